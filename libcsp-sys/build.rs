@@ -20,6 +20,7 @@ pub fn main() {
     }
 
     println!("cargo:rerun-if-changed=wrapper.h");
+    println!("cargo:rerun-if-changed=c_src");
 
     let libcsp = pkg_config::probe_library("libcsp").expect("Could not find libcsp via pkg-config");
 
@@ -28,6 +29,14 @@ pub fn main() {
         println!("cargo:warning=Found libcsp include path: {}", path.display());
     }
 
+    // Compile the C shims, for the print hook LibCSP calls back into and
+    // for the connection fields LibCSP keeps private
+    cc::Build::new()
+        .file("c_src/csp_rs_print.c")
+        .file("c_src/csp_rs_conn.c")
+        .includes(&libcsp.include_paths)
+        .compile("csp_rs_shims");
+
     let mut builder = bindgen::Builder::default()
         .header("wrapper.h")
         // This is important: tell bindgen to use the include paths from pkg-config
@@ -39,6 +48,8 @@ pub fn main() {
     if cfg!(feature = "zmq") { builder = builder.clang_arg("-DCSP_RS_ZMQ"); }
     if cfg!(feature = "socketcan") { builder = builder.clang_arg("-DCSP_RS_SOCKETCAN"); }
     if cfg!(feature = "usart") { builder = builder.clang_arg("-DCSP_RS_USART"); }
+    if cfg!(feature = "udp") { builder = builder.clang_arg("-DCSP_RS_UDP"); }
+    if cfg!(feature = "yaml") { builder = builder.clang_arg("-DCSP_RS_YAML"); }
 
     // Also include standard include paths from the system/nix environment
     if let Ok(c_include_path) = std::env::var("C_INCLUDE_PATH") {
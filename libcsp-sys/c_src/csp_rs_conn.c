@@ -0,0 +1,26 @@
+#include <csp/arch/csp_queue.h>
+
+#include "csp_rs_conn.h"
+
+/* The leading fields of `struct csp_conn_s` in LibCSP's private `src/csp_conn.h`,
+ * which isn't installed. Only these are read, so the rest of the struct, which
+ * depends on the build options, doesn't need to match. Keep in step with LibCSP. */
+struct csp_rs_conn_head {
+	int type; /* atomic_int, CONN_CLIENT or CONN_SERVER */
+	int state; /* atomic_int, CONN_CLOSED or CONN_OPEN */
+	csp_id_t idin;
+	csp_id_t idout;
+	uint8_t sport_outgoing;
+	csp_queue_handle_t rx_queue;
+};
+
+/* `csp_conn_type_t` in `src/csp_conn.h` */
+#define CSP_RS_CONN_SERVER 1
+
+int csp_rs_conn_rx_queue_size(csp_conn_t * conn) {
+	return csp_queue_size(((struct csp_rs_conn_head *)conn)->rx_queue);
+}
+
+bool csp_rs_conn_is_server(csp_conn_t * conn) {
+	return ((struct csp_rs_conn_head *)conn)->type == CSP_RS_CONN_SERVER;
+}
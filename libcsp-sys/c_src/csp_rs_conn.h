@@ -0,0 +1,11 @@
+#pragma once
+
+#include <stdbool.h>
+
+#include <csp/csp_types.h>
+
+/* Number of packets waiting in the rx queue of `conn`. */
+int csp_rs_conn_rx_queue_size(csp_conn_t * conn);
+
+/* Whether `conn` was accepted on a socket, rather than opened with `csp_connect`. */
+bool csp_rs_conn_is_server(csp_conn_t * conn);
@@ -0,0 +1,30 @@
+#include <stdarg.h>
+#include <stdio.h>
+
+#include "csp_rs_print.h"
+
+#define CSP_RS_PRINT_BUF_SIZE 512
+
+static csp_rs_print_hook_t csp_rs_print_hook = NULL;
+
+void csp_rs_print_hook_set(csp_rs_print_hook_t hook) {
+	csp_rs_print_hook = hook;
+}
+
+/* Overrides the weak default in LibCSP, which writes straight to stdout.
+ * Rust can't define variadic functions, so the message is formatted here. */
+void csp_print_func(const char * fmt, ...) {
+	va_list args;
+	va_start(args, fmt);
+
+	csp_rs_print_hook_t hook = csp_rs_print_hook;
+	if (hook == NULL) {
+		vprintf(fmt, args);
+	} else {
+		char buf[CSP_RS_PRINT_BUF_SIZE];
+		vsnprintf(buf, sizeof(buf), fmt, args);
+		hook(buf);
+	}
+
+	va_end(args);
+}
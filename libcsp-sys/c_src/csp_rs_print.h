@@ -0,0 +1,7 @@
+#pragma once
+
+/* Receives one formatted chunk of LibCSP print output. */
+typedef void (*csp_rs_print_hook_t)(const char * msg);
+
+/* Redirects `csp_print` output to `hook`. Passing NULL restores printing to stdout. */
+void csp_rs_print_hook_set(csp_rs_print_hook_t hook);
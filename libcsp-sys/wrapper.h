@@ -2,6 +2,9 @@
 #include <csp/csp_types.h>
 #include <csp/csp_id.h>
 #include <csp/csp_rtable.h>
+#include <csp/csp_debug.h>
+#include <csp/csp_iflist.h>
+#include <csp/csp_sfp.h>
 #include <csp/interfaces/csp_if_lo.h>
 
 #ifdef CSP_RS_USART
@@ -12,6 +15,17 @@
 #include <csp/drivers/can_socketcan.h>
 #endif
 
+#ifdef CSP_RS_UDP
+#include <csp/interfaces/csp_if_udp.h>
+#endif
+
+#ifdef CSP_RS_YAML
+#include <csp/csp_yaml.h>
+#endif
+
 #ifdef CSP_RS_ZMQ
 #include <csp/interfaces/csp_if_zmqhub.h>
 #endif
+
+#include "c_src/csp_rs_print.h"
+#include "c_src/csp_rs_conn.h"
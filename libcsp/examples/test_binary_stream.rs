@@ -3,7 +3,7 @@ use libcsp::{
 };
 
 use std::{
-    io::{Read, Write},
+    io::{ErrorKind, Read, Write},
     thread,
     time::Duration,
 };
@@ -31,9 +31,16 @@ fn server_task(instance: &LibCspInstance, stream: &[u8], buffer_sizes: &[usize])
 
             let mut read_result = Vec::new();
 
-            // Read until connection is closed
+            // Read until the client stops sending, without RDP the connection is never closed
             loop {
-                let bytes_read = reader.read(&mut buffer).unwrap();
+                let bytes_read = match reader.read(&mut buffer) {
+                    Ok(bytes_read) => bytes_read,
+                    Err(e) if e.kind() == ErrorKind::TimedOut => {
+                        println!("No more data");
+                        break;
+                    }
+                    Err(e) => panic!("Read failed: {}", e),
+                };
                 if bytes_read == 0 {
                     println!("Connection closed");
                     break;
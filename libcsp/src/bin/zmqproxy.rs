@@ -0,0 +1,100 @@
+//! A ZMQ hub for LibCSP ZMQ interfaces, in place of the `zmqproxy` program of LibCSP.
+//!
+//! ```text
+//! zmqproxy [--subscribe ENDPOINT] [--publish ENDPOINT] [--verbose] [--capture FILE]
+//! ```
+//!
+//! The capture file holds every forwarded message as a 2 byte big-endian length,
+//! followed by the message.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    process::ExitCode,
+};
+
+use libcsp::zmqproxy::CspZmqProxy;
+
+const USAGE: &str = "\
+Usage: zmqproxy [OPTIONS]
+
+Options:
+    -s, --subscribe ENDPOINT  Where interfaces publish to [default: tcp://*:6000]
+    -p, --publish ENDPOINT    Where interfaces subscribe to [default: tcp://*:7000]
+    -v, --verbose             Logs every forwarded message to stderr
+    -c, --capture FILE        Writes every forwarded message to FILE
+    -h, --help                Prints this help";
+
+/// Prints log records to stderr.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        eprintln!("[{}] {}", record.level(), record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("zmqproxy: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut proxy = CspZmqProxy::new();
+    let mut verbose = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("`{}` needs a value\n\n{}", arg, USAGE))
+        };
+        match arg.as_str() {
+            "-s" | "--subscribe" => proxy = proxy.subscribe_endpoint(value()?),
+            "-p" | "--publish" => proxy = proxy.publish_endpoint(value()?),
+            "-v" | "--verbose" => verbose = true,
+            "-c" | "--capture" => {
+                let path = value()?;
+                let file = File::create(&path)
+                    .map_err(|e| format!("Failed to create `{}`: {}", path, e))?;
+                proxy = proxy.capture(capture_to(BufWriter::new(file)));
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => return Err(format!("Unknown argument `{}`\n\n{}", arg, USAGE)),
+        }
+    }
+
+    if verbose {
+        log::set_logger(&StderrLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+    }
+
+    proxy.log_traffic(verbose).run().map_err(|e| e.to_string())
+}
+
+fn capture_to(mut writer: impl Write + Send + 'static) -> impl FnMut(&[u8]) + Send + 'static {
+    move |message| {
+        let length = u16::try_from(message.len()).unwrap_or(u16::MAX);
+        let written = writer
+            .write_all(&length.to_be_bytes())
+            .and_then(|_| writer.write_all(&message[..length as usize]))
+            .and_then(|_| writer.flush());
+        if let Err(e) = written {
+            log::error!(target: "libcsp::zmqproxy", "Failed to write capture: {}", e);
+        }
+    }
+}
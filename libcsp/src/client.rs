@@ -1,12 +1,16 @@
-use std::time::Duration;
+use std::{
+    os::raw::{c_int, c_void},
+    time::Duration,
+};
 
 use libcsp_sys::{
-    csp_connect, csp_ping, CSP_O_NONE,
+    csp_buffer_data_size, csp_connect, csp_ping, csp_transaction_persistent,
+    csp_transaction_w_opts,
 };
 
 use crate::{
-    errors::csp_assert, CspConnAddress, CspConnPriority, CspError, CspErrorKind, LibCspConfig,
-    CspConnection,
+    errors::csp_assert, CspConnAddress, CspConnKind, CspConnOptions, CspConnPriority,
+    CspError, CspErrorKind, LibCspConfig, CspConnection, RdpConfig,
 };
 
 pub struct CspClient {}
@@ -26,12 +30,31 @@ impl CspClient {
         timeout: Duration,
         size: u32,
     ) -> Result<u32, CspError> {
+        self.ping_opts(address, timeout, size, CspConnOptions::empty())
+    }
+
+    /// Pings with the given connection options.
+    ///
+    /// LibCSP only takes the lower 8 bits of the options for pings, so `CspConnOptions::SAME`
+    /// can't be used here.
+    pub fn ping_opts(
+        &self,
+        address: u16,
+        timeout: Duration,
+        size: u32,
+        opts: CspConnOptions,
+    ) -> Result<u32, CspError> {
+        let opts = u8::try_from(opts.check()?.bits()).map_err(|_| CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("Connection options {:?} can't be used for a ping", opts),
+        })?;
+
         unsafe {
             let result = csp_ping(
                 address,
                 timeout.as_millis() as u32,
                 size,
-                CSP_O_NONE as u8,
+                opts,
             );
 
             if result < 0 {
@@ -49,7 +72,7 @@ impl CspClient {
         priority: CspConnPriority,
         timeout: Duration,
     ) -> Result<CspConnection, CspError> {
-        self.connect_opts(address, priority, timeout, CSP_O_NONE)
+        self.connect_opts(address, priority, timeout, CspConnOptions::empty())
     }
 
     pub fn connect_opts(
@@ -57,15 +80,17 @@ impl CspClient {
         address: CspConnAddress,
         priority: CspConnPriority,
         timeout: Duration,
-        opts: u32,
+        opts: CspConnOptions,
     ) -> Result<CspConnection, CspError> {
+        let opts = opts.check()?;
+
         unsafe {
             let connection = csp_connect(
                 priority as u8,
                 address.address,
                 address.port,
                 timeout.as_millis() as u32,
-                opts,
+                opts.bits(),
             );
 
             if connection.is_null() {
@@ -75,7 +100,126 @@ impl CspClient {
                 });
             }
 
-            Ok(CspConnection::new(connection, 1000)) // Default service timeout
+            Ok(CspConnection::new(connection, CspConnKind::Client, 1000)) // Default service timeout
         }
     }
+
+    /// Sends `request` on a new connection, waits for a single reply and closes the connection.
+    ///
+    /// With `reply_len`, a reply of any other length is a `CspErrorKind::ReplyLength` error,
+    /// and with `Some(0)` no reply is waited for. LibCSP reports a failed connection, and
+    /// an empty reply, the same way as a timeout, which is a `CspErrorKind::Timedout` error.
+    pub fn transaction(
+        &self,
+        address: CspConnAddress,
+        priority: CspConnPriority,
+        request: &[u8],
+        reply_len: Option<usize>,
+        timeout: Duration,
+        opts: CspConnOptions,
+    ) -> Result<Vec<u8>, CspError> {
+        let opts = opts.check()?;
+        transaction(request, reply_len, timeout, |request, inbuf, inlen| unsafe {
+            csp_transaction_w_opts(
+                priority as u8,
+                address.address,
+                address.port,
+                timeout.as_millis() as u32,
+                request.as_ptr() as *mut _,
+                request.len() as c_int,
+                inbuf,
+                inlen,
+                opts.bits(),
+            )
+        })
+    }
+
+    /// Like `transaction`, on a connection that stays open for further transactions.
+    pub fn transaction_persistent(
+        &self,
+        connection: &CspConnection,
+        request: &[u8],
+        reply_len: Option<usize>,
+        timeout: Duration,
+    ) -> Result<Vec<u8>, CspError> {
+        transaction(request, reply_len, timeout, |request, inbuf, inlen| unsafe {
+            csp_transaction_persistent(
+                connection.connection,
+                timeout.as_millis() as u32,
+                request.as_ptr() as *mut _,
+                request.len() as c_int,
+                inbuf,
+                inlen,
+            )
+        })
+    }
+
+    /// Opens an RDP connection with its own RDP parameters, instead of those of the instance.
+    ///
+    /// `CspConnOptions::RDP` is added to `opts`.
+    ///
+    /// LibCSP only has the one set of RDP parameters, so they are swapped in for the
+    /// handshake and restored afterwards. An incoming RDP connection that the router
+    /// accepts during the handshake also gets these parameters.
+    pub fn connect_rdp(
+        &self,
+        address: CspConnAddress,
+        priority: CspConnPriority,
+        timeout: Duration,
+        opts: CspConnOptions,
+        rdp: &RdpConfig,
+    ) -> Result<CspConnection, CspError> {
+        let opts = (opts | CspConnOptions::RDP).check()?;
+
+        // LibCSP takes the parameters from the instance during the handshake
+        crate::rdp::with_options(rdp, || self.connect_opts(address, priority, timeout, opts))?
+    }
+}
+
+/// Runs a LibCSP transaction, which returns the reply length, 1 when no reply is
+/// expected, or 0 on failure.
+fn transaction(
+    request: &[u8],
+    reply_len: Option<usize>,
+    timeout: Duration,
+    f: impl FnOnce(&[u8], *mut c_void, c_int) -> c_int,
+) -> Result<Vec<u8>, CspError> {
+    let max_size = unsafe { csp_buffer_data_size() };
+    if request.len() > max_size || reply_len.is_some_and(|len| len > max_size) {
+        return Err(CspError {
+            kind: CspErrorKind::Inval,
+            message: format!(
+                "Transaction request or reply exceeds maximum buffer size {}",
+                max_size
+            ),
+        });
+    }
+
+    // LibCSP copies a reply of any length when given -1, so the buffer fits the largest one
+    let mut reply = vec![0u8; max_size];
+    let inlen = if reply_len == Some(0) { 0 } else { -1 };
+    let result = f(request, reply.as_mut_ptr() as *mut c_void, inlen);
+
+    if reply_len == Some(0) && result == 1 {
+        return Ok(Vec::new());
+    }
+    if result <= 0 {
+        return Err(CspError {
+            kind: CspErrorKind::Timedout,
+            message: format!("No reply within {:?}", timeout),
+        });
+    }
+
+    reply.truncate(result as usize);
+    match reply_len {
+        Some(expected) if expected != reply.len() => Err(CspError {
+            kind: CspErrorKind::ReplyLength,
+            message: format!(
+                "Expected a {} byte reply, got {} bytes",
+                expected,
+                reply.len()
+            ),
+        }),
+        _ => Ok(reply),
+    }
 }
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use libcsp_sys::CSP_NO_VIA_ADDRESS;
+use serde::Deserialize;
+
+use crate::{
+    interface::CspZmqConfig, CspConnOptions, CspDebugChannel, CspError, CspErrorKind,
+    LibCspBuilder, LibCspConfig, LibCspInstance, Route,
+};
+
+/// A complete node description, meant to be deserialized from TOML, JSON or any other
+/// serde format, so that a node can be reconfigured without a rebuild.
+///
+/// ```toml
+/// address = 1
+/// hostname = "ground-station"
+/// debug_channels = ["error", "warn"]
+///
+/// [[interfaces]]
+/// type = "zmq"
+/// host = "localhost"
+/// routes = [{ address = 0, netmask = 0 }]
+/// ```
+///
+/// Fields that are left out take the defaults of `LibCspConfig`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeConfig {
+    pub address: u16,
+    pub hostname: Option<String>,
+    pub model: Option<String>,
+    pub revision: Option<String>,
+    pub dedup: Option<u8>,
+    pub connection_backlog: Option<usize>,
+
+    /// Default connection options, written like `"RDP | CRC32"`
+    pub conn_dfl_so: Option<CspConnOptions>,
+
+    /// Packet timeout on service messages that are handled internally, in milliseconds
+    pub service_timeout_ms: Option<u64>,
+
+    #[serde(default = "default_debug_channels")]
+    pub debug_channels: Vec<CspDebugChannel>,
+
+    #[serde(default)]
+    pub interfaces: Vec<InterfaceConfig>,
+}
+
+fn default_debug_channels() -> Vec<CspDebugChannel> {
+    CspDebugChannel::up_to_info().to_vec()
+}
+
+/// An interface, together with the routes that go through it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct InterfaceConfig {
+    #[serde(flatten)]
+    pub kind: InterfaceKind,
+
+    #[serde(default)]
+    pub routes: Vec<RouteConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InterfaceKind {
+    /// See `CspZmqConfig`, with either a `host` or both endpoints
+    #[serde(alias = "zmq_endpoints")]
+    Zmq(CspZmqConfig),
+}
+
+/// The serialized form of a `Route`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    pub address: u16,
+    /// Number of netmask bits, all bits if left out
+    pub netmask: Option<u8>,
+    pub via: Option<u16>,
+}
+
+impl From<RouteConfig> for Route {
+    fn from(config: RouteConfig) -> Self {
+        let mut route = Route::new(config.address);
+        if let Some(bits) = config.netmask {
+            route = route.netmask_bits(bits);
+        }
+        route.via(config.via.unwrap_or(CSP_NO_VIA_ADDRESS as u16))
+    }
+}
+
+impl NodeConfig {
+    /// The `LibCspConfig` part of the node description.
+    pub fn libcsp_config(&self) -> LibCspConfig {
+        let defaults = LibCspConfig::default();
+        LibCspConfig {
+            address: self.address,
+            hostname: self.hostname.clone().unwrap_or(defaults.hostname),
+            model: self.model.clone().unwrap_or(defaults.model),
+            revision: self.revision.clone().unwrap_or(defaults.revision),
+            dedup: self.dedup.unwrap_or(defaults.dedup),
+            conn_dfl_so: self.conn_dfl_so.unwrap_or(defaults.conn_dfl_so),
+            connection_backlog: self
+                .connection_backlog
+                .unwrap_or(defaults.connection_backlog),
+            rdp: defaults.rdp,
+            service_timeout: self
+                .service_timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.service_timeout),
+        }
+    }
+
+    /// Checks the parts of the description that LibCSP would otherwise only reject after starting.
+    pub fn validate(&self) -> Result<(), CspError> {
+        if let Some(opts) = self.conn_dfl_so {
+            opts.check()?;
+        }
+
+        for (index, interface) in self.interfaces.iter().enumerate() {
+            match &interface.kind {
+                InterfaceKind::Zmq(zmq) => zmq.validate()?,
+            }
+
+            if interface.routes.is_empty() {
+                return Err(CspError {
+                    kind: CspErrorKind::Inval,
+                    message: format!("Interface #{} has no routes", index + 1),
+                });
+            }
+
+            for route in &interface.routes {
+                if route.netmask.is_some_and(|bits| bits > 14) {
+                    return Err(CspError {
+                        kind: CspErrorKind::Inval,
+                        message: format!(
+                            "Interface #{}: route to {} has a netmask larger than 14 bits",
+                            index + 1,
+                            route.address
+                        ),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts the global LibCSP instance with its interfaces and routes.
+    pub fn build(&self) -> Result<LibCspInstance, CspError> {
+        self.validate()?;
+
+        let instance = LibCspBuilder::new(self.libcsp_config())
+            .debug_channels(&self.debug_channels)
+            .try_build()?;
+
+        for interface in &self.interfaces {
+            let handle = match &interface.kind {
+                InterfaceKind::Zmq(zmq) => instance.add_interface(zmq.clone())?,
+            };
+
+            // All the routes share the one interface
+            for route in interface.routes.iter().copied() {
+                instance.add_route(route.into(), &handle)?;
+            }
+        }
+
+        Ok(instance)
+    }
+}
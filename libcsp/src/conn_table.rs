@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use libcsp_sys::{
+    csp_conn_dport, csp_conn_dst, csp_conn_flags, csp_conn_is_active, csp_conn_sport, csp_conn_src,
+    csp_conn_t, csp_rs_conn_is_server, csp_rs_conn_rx_queue_size, CSP_FCRC32, CSP_FHMAC, CSP_FRDP,
+};
+
+use crate::{CspConnAddress, CspConnOptions};
+
+/// Which side opened a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CspConnKind {
+    /// Opened with `CspClient::connect`
+    Client,
+    /// Accepted on a `CspSocket`
+    Server,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CspConnState {
+    Open,
+    /// Closed by LibCSP, e.g. by the remote end or an RDP timeout,
+    /// while the `CspConnection` is still held.
+    Closed,
+}
+
+/// A snapshot of a connection in the LibCSP connection pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CspConnInfo {
+    pub kind: CspConnKind,
+    pub state: CspConnState,
+    pub src: CspConnAddress,
+    pub dst: CspConnAddress,
+    /// The options in use, as seen in the packet header flags
+    pub opts: CspConnOptions,
+    /// Packets received and waiting to be read
+    pub rx_queue: usize,
+    /// Whether a `CspConnection` holds the connection. An open connection that isn't
+    /// held is waiting to be accepted, or was leaked.
+    pub held: bool,
+    /// Time since the connection was opened or accepted, only known for held connections
+    pub age: Option<Duration>,
+}
+
+impl CspConnInfo {
+    /// Reads a connection from the pool, with the kind and age known to its holder if any.
+    pub(crate) unsafe fn read(
+        connection: *mut csp_conn_t,
+        holder: Option<(CspConnKind, Duration)>,
+    ) -> Self {
+        let flags = csp_conn_flags(connection) as u32;
+        let mut opts = CspConnOptions::empty();
+        opts.set(CspConnOptions::RDP, flags & CSP_FRDP != 0);
+        opts.set(CspConnOptions::HMAC, flags & CSP_FHMAC != 0);
+        opts.set(CspConnOptions::CRC32, flags & CSP_FCRC32 != 0);
+
+        let kind = match holder {
+            Some((kind, _)) => kind,
+            None if csp_rs_conn_is_server(connection) => CspConnKind::Server,
+            None => CspConnKind::Client,
+        };
+
+        Self {
+            kind,
+            state: if csp_conn_is_active(connection) {
+                CspConnState::Open
+            } else {
+                CspConnState::Closed
+            },
+            src: CspConnAddress {
+                address: csp_conn_src(connection) as u16,
+                port: csp_conn_sport(connection) as u8,
+            },
+            dst: CspConnAddress {
+                address: csp_conn_dst(connection) as u16,
+                port: csp_conn_dport(connection) as u8,
+            },
+            opts,
+            rx_queue: csp_rs_conn_rx_queue_size(connection).max(0) as usize,
+            held: holder.is_some(),
+            age: holder.map(|(_, age)| age),
+        }
+    }
+}
+
+/// The addresses of the slots in the LibCSP connection pool, read from the output of
+/// `csp_conn_print_table`, which is the only thing that walks the pool.
+///
+/// Each slot starts a line with its index and address, e.g. `[03 0x55d0c3a1b2c0] S:1, ...`.
+pub(crate) fn pool_slots(table: &str) -> Vec<*mut csp_conn_t> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let (_index, rest) = line.trim_start().strip_prefix('[')?.split_once(' ')?;
+            let (address, _) = rest.split_once(']')?;
+            let address =
+                usize::from_str_radix(address.trim().trim_start_matches("0x"), 16).ok()?;
+            (address != 0).then_some(address as *mut csp_conn_t)
+        })
+        .collect()
+}
@@ -1,10 +1,25 @@
-use std::{ops::Deref, ptr::NonNull, time::Duration, io::Write};
+use std::{
+    io::Write,
+    ops::Deref,
+    os::raw::{c_int, c_uint, c_void},
+    ptr::NonNull,
+    time::Duration,
+};
 use libcsp_sys::{
     csp_buffer_free, csp_conn_dport, csp_conn_dst, csp_conn_sport, csp_conn_src,
     csp_conn_t, csp_packet_t, csp_read, csp_send, csp_buffer_get, csp_buffer_data_size,
+    csp_sfp_recv_fp, csp_sfp_send_own_memcpy,
+};
+
+use crate::{
+    errors::csp_assert, handles, CspConnAddress, CspConnKind, CspConnState, CspError,
+    CspErrorKind, CspId,
 };
 
-use crate::{CspConnAddress, CspError, CspErrorKind, CspId};
+/// Size of the SFP header that LibCSP adds to every fragment, `sfp_header_t` in
+/// `csp_sfp.c`: the offset and the total size, both `uint32_t`. It isn't in a header
+/// LibCSP installs, so it has to be kept in step by hand.
+const SFP_HEADER_SIZE: usize = 8;
 
 pub struct CspConnection {
     pub src: CspConnAddress,
@@ -18,7 +33,12 @@ unsafe impl Send for CspConnection {}
 
 impl CspConnection {
     /// Internal "new" function to create a `CspConnection` from a raw pointer to a CSP connection pointer.
-    pub(crate) fn new(connection: *mut csp_conn_t, service_timeout_ms: u32) -> Self {
+    pub(crate) fn new(
+        connection: *mut csp_conn_t,
+        kind: CspConnKind,
+        service_timeout_ms: u32,
+    ) -> Self {
+        handles::register_connection(connection, kind);
         unsafe {
             Self {
                 src: CspConnAddress {
@@ -61,6 +81,28 @@ impl CspConnection {
         }
     }
 
+    /// Waits for the next packet, keeping the connection for replies.
+    pub fn read_packet(&self, timeout: Duration) -> Option<CspPacket> {
+        let packet = unsafe { csp_read(self.connection, timeout.as_millis() as u32) };
+        let packet = NonNull::new(packet)?;
+        Some(CspPacket { packet })
+    }
+
+    /// Whether the connection is still open. LibCSP closes RDP connections when the
+    /// remote end closes them or stops answering. Connections without RDP stay
+    /// active until they are dropped, or the instance is shut down.
+    pub fn is_active(&self) -> bool {
+        self.state() == CspConnState::Open
+    }
+
+    pub fn state(&self) -> CspConnState {
+        if handles::is_active(self.connection) {
+            CspConnState::Open
+        } else {
+            CspConnState::Closed
+        }
+    }
+
     pub fn iter_packets(self, timeout: Duration) -> CspConnectionPacketIter {
         CspConnectionPacketIter::new(self, timeout)
     }
@@ -130,11 +172,72 @@ impl CspConnection {
 
         Ok(())
     }
+
+    /// Sends `data` with the Small Fragmentation Protocol, in fragments of at most `mtu`
+    /// bytes, for messages larger than a single buffer.
+    ///
+    /// The fragments must fit a buffer along with the 8 byte SFP header. They are sent
+    /// as fast as buffers allow, so large messages need RDP, or a receiver that keeps up.
+    pub fn send_sfp(&self, data: &[u8], mtu: usize, timeout: Duration) -> Result<(), CspError> {
+        let max_mtu = self.max_buffer_size as usize - SFP_HEADER_SIZE;
+        if mtu == 0 || mtu > max_mtu {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: format!("SFP MTU {} is not between 1 and {}", mtu, max_mtu),
+            });
+        }
+        let size = c_uint::try_from(data.len()).map_err(|_| CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("SFP message of {} bytes is too large", data.len()),
+        })?;
+
+        unsafe {
+            let result = csp_sfp_send_own_memcpy(
+                self.connection,
+                data.as_ptr() as *const c_void,
+                size,
+                mtu as c_uint,
+                timeout.as_millis() as u32,
+                Some(sfp_memcpy),
+            );
+            csp_assert!(result, "Failed to send SFP message");
+        }
+
+        Ok(())
+    }
+
+    /// Receives a message sent with SFP, waiting up to `timeout` for each fragment.
+    pub fn recv_sfp(&self, timeout: Duration) -> Result<Vec<u8>, CspError> {
+        let mut data: *mut c_void = std::ptr::null_mut();
+        let mut size: c_int = 0;
+
+        unsafe {
+            let result = csp_sfp_recv_fp(
+                self.connection,
+                &mut data,
+                &mut size,
+                timeout.as_millis() as u32,
+                std::ptr::null_mut(),
+            );
+            csp_assert!(result, "Failed to receive SFP message");
+
+            // `csp_sfp_recv_fp` allocates the message with `csp_malloc`, which is the libc
+            // `malloc` on POSIX, and leaves it to the caller
+            let message = std::slice::from_raw_parts(data as *const u8, size as usize).to_vec();
+            libc::free(data);
+            Ok(message)
+        }
+    }
+}
+
+unsafe extern "C" fn sfp_memcpy(dest: *mut c_void, src: *const c_void, n: usize) -> *mut c_void {
+    std::ptr::copy_nonoverlapping(src as *const u8, dest as *mut u8, n);
+    dest
 }
 
 impl Drop for CspConnection {
     fn drop(&mut self) {
-        unsafe { libcsp_sys::csp_close(self.connection) };
+        handles::close_connection(self.connection);
     }
 }
 
@@ -148,7 +251,7 @@ unsafe impl Send for CspConnectionPacketIter {}
 impl CspConnectionPacketIter {
     pub fn new(connection: CspConnection, timeout: Duration) -> Self {
         Self {
-            connection: connection,
+            connection,
             timeout_ms: timeout.as_millis() as u32,
         }
     }
@@ -165,7 +268,7 @@ impl Iterator for CspConnectionPacketIter {
 }
 
 pub struct CspPacket {
-    packet: NonNull<csp_packet_t>,
+    pub(crate) packet: NonNull<csp_packet_t>,
 }
 
 unsafe impl Send for CspPacket {}
@@ -197,6 +300,11 @@ impl Drop for CspPacket {
     }
 }
 
+/// Reads the payload of the packets on a connection as a stream of bytes.
+///
+/// A read that gets no packet within the timeout fails with `io::ErrorKind::TimedOut`,
+/// and can be tried again. `Ok(0)` is only returned once the connection is closed,
+/// see `CspConnection::is_active`.
 pub struct CspConnectionPacketReader {
     connection: CspConnection,
     timeout_ms: u32,
@@ -221,34 +329,64 @@ impl CspConnectionPacketReader {
             pos: 0,
         }
     }
+
+    /// Sets how long a read waits for the next packet.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout_ms = timeout.as_millis() as u32;
+    }
+
+    pub fn connection(&self) -> &CspConnection {
+        &self.connection
+    }
+
+    /// Waits for the next packet, `None` once the connection is closed and drained.
+    fn next_packet(&mut self) -> std::io::Result<Option<NonNull<csp_packet_t>>> {
+        let packet = unsafe { csp_read(self.connection.connection, self.timeout_ms) };
+        if let Some(packet) = NonNull::new(packet) {
+            self.packet = PacketReaderState::Packet(CspPacket { packet });
+            return Ok(Some(packet));
+        }
+
+        if self.connection.is_active() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("No packet within {} ms", self.timeout_ms),
+            ));
+        }
+
+        // Packets that arrived just before the close are still read
+        let packet = unsafe { csp_read(self.connection.connection, 0) };
+        match NonNull::new(packet) {
+            Some(packet) => {
+                self.packet = PacketReaderState::Packet(CspPacket { packet });
+                Ok(Some(packet))
+            }
+            None => {
+                self.packet = PacketReaderState::Finished;
+                Ok(None)
+            }
+        }
+    }
 }
 
-impl<'a> std::io::Read for CspConnectionPacketReader {
+impl std::io::Read for CspConnectionPacketReader {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let mut read = 0;
         let mut remaining_buf = buf;
 
         loop {
-            if remaining_buf.len() == 0 {
+            if remaining_buf.is_empty() {
                 return Ok(read);
             }
 
             let next_packet = match &self.packet {
-                PacketReaderState::NoPacket => {
-                    let packet = unsafe { csp_read(self.connection.connection, self.timeout_ms) };
-                    let packet = NonNull::new(packet);
-
-                    match packet {
-                        Some(packet) => {
-                            self.packet = PacketReaderState::Packet(CspPacket { packet });
-                            packet
-                        }
-                        None => {
-                            self.packet = PacketReaderState::Finished;
-                            return Ok(read);
-                        }
-                    }
-                }
+                PacketReaderState::NoPacket => match self.next_packet() {
+                    Ok(Some(packet)) => packet,
+                    Ok(None) => return Ok(read),
+                    // What was read so far is returned first, the timeout comes with the next read
+                    Err(_) if read > 0 => return Ok(read),
+                    Err(e) => return Err(e),
+                },
                 PacketReaderState::Packet(packet) => packet.packet,
                 PacketReaderState::Finished => return Ok(read),
             };
@@ -287,7 +425,7 @@ impl std::io::Write for CspConnectionWriter {
         let mut remaining_buf = buf;
         let mut written = 0;
         loop {
-            if remaining_buf.len() == 0 {
+            if remaining_buf.is_empty() {
                 return Ok(written);
             }
 
@@ -298,8 +436,7 @@ impl std::io::Write for CspConnectionWriter {
                         let packet = csp_buffer_get(self.connection.max_buffer_size as usize)
                             as *mut csp_packet_t;
                         if packet.is_null() {
-                            return Err(std::io::Error::new(
-                                std::io::ErrorKind::Other,
+                            return Err(std::io::Error::other(
                                 "Failed to get CSP buffer, no buffers left in the buffer pool.",
                             ));
                         }
@@ -0,0 +1,135 @@
+use std::{ptr::NonNull, time::Duration};
+
+use libcsp_sys::{
+    csp_buffer_data_size, csp_buffer_get, csp_packet_t, csp_recvfrom, csp_sendto, csp_socket_t,
+};
+
+use crate::{
+    handles, CspConnOptions, CspConnPriority, CspError, CspErrorKind, CspId, CspPacket, CspPort,
+};
+
+/// A connectionless socket, for beacons, telemetry and other traffic that
+/// doesn't need a connection.
+///
+/// Packets are sent from the port the socket is bound to, and every packet
+/// arriving on that port is received, whoever sent it.
+pub struct CspDatagramSocket {
+    port: CspPort,
+    max_buffer_size: usize,
+    socket: NonNull<csp_socket_t>,
+}
+
+unsafe impl Send for CspDatagramSocket {}
+
+impl CspDatagramSocket {
+    pub(crate) fn from_ptr(socket: *mut csp_socket_t, port: CspPort) -> Self {
+        handles::register_socket(socket);
+        Self {
+            port,
+            max_buffer_size: unsafe { csp_buffer_data_size() },
+            socket: NonNull::new(socket).expect("Socket pointer cannot be null"),
+        }
+    }
+
+    pub fn port(&self) -> CspPort {
+        self.port
+    }
+
+    /// Sends `data` in a single packet to `port` on `address`.
+    pub fn send_to(
+        &self,
+        address: u16,
+        port: u8,
+        data: &[u8],
+        priority: CspConnPriority,
+    ) -> Result<(), CspError> {
+        self.send_to_opts(address, port, data, priority, CspConnOptions::empty())
+    }
+
+    /// Like `send_to`, with the given options. RDP needs a connection, so
+    /// `CspConnOptions::RDP` can't be used here.
+    pub fn send_to_opts(
+        &self,
+        address: u16,
+        port: u8,
+        data: &[u8],
+        priority: CspConnPriority,
+        opts: CspConnOptions,
+    ) -> Result<(), CspError> {
+        let opts = opts.check()?;
+        if opts.intersects(CspConnOptions::RDP | CspConnOptions::SAME) {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: format!("Connection options {:?} can't be used for datagrams", opts),
+            });
+        }
+        if self.port == CspPort::any_port() {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: "A socket bound to any port has no port to send from".to_string(),
+            });
+        }
+        if data.len() > self.max_buffer_size {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: format!(
+                    "Data length {} exceeds maximum buffer size {}",
+                    data.len(),
+                    self.max_buffer_size
+                ),
+            });
+        }
+
+        unsafe {
+            let packet = csp_buffer_get(data.len()) as *mut csp_packet_t;
+            if packet.is_null() {
+                return Err(CspError {
+                    kind: CspErrorKind::Nomem,
+                    message: "Failed to get CSP buffer".to_string(),
+                });
+            }
+
+            let data_ptr = &mut (*packet).__bindgen_anon_2.data as *mut _ as *mut u8;
+            std::ptr::copy_nonoverlapping(data.as_ptr(), data_ptr, data.len());
+            (*packet).length = data.len() as u16;
+
+            // LibCSP takes the packet, and frees it if it can't be sent
+            csp_sendto(
+                priority as u8,
+                address,
+                port,
+                self.port.as_u8(),
+                opts.bits(),
+                packet,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Waits for a packet, and returns it with the header it arrived with,
+    /// or `None` if the timeout expires.
+    pub fn recv_from(&self, timeout: Duration) -> Option<(CspPacket, CspId)> {
+        let packet = unsafe { csp_recvfrom(self.socket.as_ptr(), timeout.as_millis() as u32) };
+        let packet = CspPacket {
+            packet: NonNull::new(packet)?,
+        };
+        let id = packet.id();
+        Some((packet, id))
+    }
+
+    /// Receives packets until one takes longer than `timeout` to arrive.
+    pub fn iter_packets(&self, timeout: Duration) -> impl Iterator<Item = (CspPacket, CspId)> + '_ {
+        std::iter::from_fn(move || self.recv_from(timeout))
+    }
+}
+
+impl Drop for CspDatagramSocket {
+    fn drop(&mut self) {
+        handles::close_socket(self.socket.as_ptr());
+        unsafe {
+            // The memory was allocated with Box::into_raw in lib.rs
+            let _ = Box::from_raw(self.socket.as_ptr());
+        }
+    }
+}
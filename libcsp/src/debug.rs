@@ -0,0 +1,235 @@
+use std::{
+    cell::{Cell, RefCell},
+    ffi::CStr,
+    os::raw::c_char,
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use libcsp_sys::{csp_dbg_packet_print, csp_dbg_rdp_print, csp_rs_print_hook_set};
+use log::Level;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CspDebugChannel {
+    /// Error, i.e. RDP errors
+    Error = 0,
+    /// Warning. LibCSP v2.0 doesn't tag any line as a warning,
+    /// so nothing is logged on this channel.
+    Warn = 1,
+    /// Informational, i.e. every line LibCSP prints without a debug macro,
+    /// such as configuration problems
+    Info = 2,
+    /// Buffer, e.g. csp_packet get/free. LibCSP v2.0 has no such traces,
+    /// so nothing is logged on this channel.
+    Buffer = 3,
+    /// Packet routing
+    Packet = 4,
+    /// Protocol, i.e. RDP
+    Protocol = 5,
+    /// Locking, i.e. semaphore. LibCSP v2.0 has no such traces,
+    /// so nothing is logged on this channel.
+    Lock = 6,
+}
+
+impl CspDebugChannel {
+    pub fn all() -> &'static [Self; 7] {
+        &[
+            Self::Error,
+            Self::Warn,
+            Self::Info,
+            Self::Buffer,
+            Self::Packet,
+            Self::Protocol,
+            Self::Lock,
+        ]
+    }
+
+    pub fn up_to(self) -> &'static [Self] {
+        &Self::all()[..=self as usize]
+    }
+
+    pub fn up_to_error() -> &'static [Self] {
+        Self::up_to(Self::Error)
+    }
+
+    pub fn up_to_warn() -> &'static [Self] {
+        Self::up_to(Self::Warn)
+    }
+
+    pub fn up_to_info() -> &'static [Self] {
+        Self::up_to(Self::Info)
+    }
+
+    /// The `log` level that messages on this channel are emitted with.
+    pub fn level(self) -> Level {
+        match self {
+            Self::Error => Level::Error,
+            Self::Warn => Level::Warn,
+            Self::Info => Level::Info,
+            Self::Packet | Self::Protocol => Level::Debug,
+            Self::Buffer | Self::Lock => Level::Trace,
+        }
+    }
+
+    /// The `log` target that messages on this channel are emitted with.
+    pub fn target(self) -> &'static str {
+        match self {
+            Self::Error | Self::Warn | Self::Info => "libcsp",
+            Self::Buffer => "libcsp::buffer",
+            Self::Packet => "libcsp::packet",
+            Self::Protocol => "libcsp::protocol",
+            Self::Lock => "libcsp::lock",
+        }
+    }
+
+    /// Returns the channel of a line printed by LibCSP, and the line without its colour codes.
+    ///
+    /// LibCSP v2.0 prints everything through `csp_print`. Its debug macros colour their
+    /// lines: red for RDP errors, blue for the RDP trace and green for the packet trace.
+    /// Lines without a colour are plain prints, they go to the info channel.
+    fn of_line(line: &str) -> (Self, String) {
+        // The colour is reset after the newline, so the reset starts the next line
+        let line = line.trim_start_matches(RESET);
+        let channel = if line.starts_with(RED) {
+            Self::Error
+        } else if line.starts_with(BLUE) {
+            Self::Protocol
+        } else if line.starts_with(GREEN) {
+            Self::Packet
+        } else {
+            Self::Info
+        };
+        (channel, strip_escapes(line))
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const BLUE: &str = "\x1b[34m";
+
+/// Removes the ANSI escape sequences from `line`.
+fn strip_escapes(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to and including the final byte of the sequence, e.g. `m`
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+thread_local! {
+    /// Partially printed line, LibCSP sometimes builds a line out of several prints.
+    static LINE_BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+
+    /// Set while one of the `print_*` functions runs, their output is meant for stdout.
+    static PASSTHROUGH: Cell<bool> = const { Cell::new(false) };
+
+    /// Collects the output while `capture` runs.
+    static CAPTURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// The selected channels, one bit per channel.
+static SELECTED_CHANNELS: AtomicU8 = AtomicU8::new(0);
+
+/// Forwards the LibCSP print output on the selected channels into the `log` ecosystem,
+/// and enables the C-side traces for the packet and protocol channels.
+///
+/// Lines on channels that weren't selected are dropped.
+pub(crate) fn install(channels: &[CspDebugChannel]) {
+    let selected = channels
+        .iter()
+        .fold(0, |bits, &channel| bits | 1 << channel as u8);
+    SELECTED_CHANNELS.store(selected, Ordering::Relaxed);
+
+    // LibCSP prints the RDP errors from level 1, and the whole RDP trace from level 2
+    let rdp_print = if channels.contains(&CspDebugChannel::Protocol) {
+        2
+    } else {
+        channels.contains(&CspDebugChannel::Error) as u8
+    };
+    unsafe {
+        csp_dbg_packet_print = channels.contains(&CspDebugChannel::Packet) as u8;
+        csp_dbg_rdp_print = rdp_print;
+        csp_rs_print_hook_set(Some(print_hook));
+    }
+}
+
+/// Runs `f` with the LibCSP print output going straight to stdout rather than the logger.
+pub(crate) fn with_stdout<R>(f: impl FnOnce() -> R) -> R {
+    PASSTHROUGH.with(|passthrough| passthrough.set(true));
+    let _reset = ResetOnDrop;
+    f()
+}
+
+/// Runs `f` and returns the LibCSP print output it produced, instead of logging it.
+pub(crate) fn capture(f: impl FnOnce()) -> String {
+    CAPTURE.with(|capture| *capture.borrow_mut() = Some(String::new()));
+    let _reset = ResetOnDrop;
+    f();
+    CAPTURE
+        .with(|capture| capture.borrow_mut().take())
+        .unwrap_or_default()
+}
+
+/// Sends the print output back to the logger, also when `f` panics.
+struct ResetOnDrop;
+
+impl Drop for ResetOnDrop {
+    fn drop(&mut self) {
+        PASSTHROUGH.with(|passthrough| passthrough.set(false));
+        CAPTURE.with(|capture| capture.borrow_mut().take());
+    }
+}
+
+unsafe extern "C" fn print_hook(msg: *const c_char) {
+    let msg = CStr::from_ptr(msg).to_string_lossy();
+
+    // A panic, e.g. in the logger, must not unwind into LibCSP
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| forward(&msg)));
+}
+
+fn forward(msg: &str) {
+    if PASSTHROUGH.with(|passthrough| passthrough.get()) {
+        print!("{}", msg);
+        return;
+    }
+    let captured = CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+        Some(output) => {
+            output.push_str(msg);
+            true
+        }
+        None => false,
+    });
+    if captured {
+        return;
+    }
+
+    LINE_BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.push_str(msg);
+
+        while let Some(end) = buffer.find('\n') {
+            let line: String = buffer.drain(..=end).collect();
+            let (channel, line) = CspDebugChannel::of_line(&line);
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            if SELECTED_CHANNELS.load(Ordering::Relaxed) & 1 << channel as u8 == 0 {
+                continue;
+            }
+            log::log!(target: channel.target(), channel.level(), "{}", line);
+        }
+    });
+}
@@ -20,6 +20,8 @@ pub enum CspErrorKind {
     Unknown(i32) = 1,
     NoBuffersAvailable = 2,
     FailedToSend = 3,
+    /// A transaction reply that isn't the expected length
+    ReplyLength = 4,
 }
 
 impl std::error::Error for CspErrorKind {}
@@ -46,6 +48,7 @@ impl std::fmt::Display for CspErrorKind {
             CspErrorKind::Unknown(code) => write!(f, "Unknown error code: {}", code),
             CspErrorKind::NoBuffersAvailable => write!(f, "No buffers available"),
             CspErrorKind::FailedToSend => write!(f, "Failed to send packet"),
+            CspErrorKind::ReplyLength => write!(f, "Unexpected reply length"),
         }
     }
 }
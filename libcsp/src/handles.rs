@@ -0,0 +1,100 @@
+use std::{sync::Mutex, time::Instant};
+
+use libcsp_sys::{
+    csp_close, csp_conn_is_active, csp_conn_print_table, csp_conn_t, csp_socket_close, csp_socket_t,
+};
+
+use crate::{conn_table, debug, CspConnInfo, CspConnKind};
+
+struct OpenConnection {
+    connection: usize,
+    kind: CspConnKind,
+    opened: Instant,
+}
+
+/// Sockets and connections that are still open on the C side, stored as addresses.
+///
+/// Both the owning Rust handle and `LibCspInstance::shutdown` may close them,
+/// this makes sure whichever comes first is the only one to do so.
+static OPEN_SOCKETS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+static OPEN_CONNECTIONS: Mutex<Vec<OpenConnection>> = Mutex::new(Vec::new());
+
+fn take<T>(list: &Mutex<Vec<T>>, is_match: impl Fn(&T) -> bool) -> bool {
+    let mut list = list.lock().unwrap();
+    match list.iter().position(is_match) {
+        Some(index) => {
+            list.swap_remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn register_socket(socket: *mut csp_socket_t) {
+    OPEN_SOCKETS.lock().unwrap().push(socket as usize);
+}
+
+pub(crate) fn register_connection(connection: *mut csp_conn_t, kind: CspConnKind) {
+    OPEN_CONNECTIONS.lock().unwrap().push(OpenConnection {
+        connection: connection as usize,
+        kind,
+        opened: Instant::now(),
+    });
+}
+
+/// Closes the socket, unless it was already closed by a shutdown.
+/// The memory of the socket is still owned by the caller.
+pub(crate) fn close_socket(socket: *mut csp_socket_t) {
+    if take(&OPEN_SOCKETS, |&p| p == socket as usize) {
+        unsafe { csp_socket_close(socket) };
+    }
+}
+
+/// Closes the connection, unless it was already closed by a shutdown.
+pub(crate) fn close_connection(connection: *mut csp_conn_t) {
+    if take(&OPEN_CONNECTIONS, |open| {
+        open.connection == connection as usize
+    }) {
+        unsafe { csp_close(connection) };
+    }
+}
+
+/// Whether LibCSP still considers the connection open. A connection closed by
+/// a shutdown is never active, even if LibCSP reused its slot.
+pub(crate) fn is_active(connection: *mut csp_conn_t) -> bool {
+    let open = OPEN_CONNECTIONS.lock().unwrap();
+    open.iter().any(|open| open.connection == connection as usize)
+        && unsafe { csp_conn_is_active(connection) }
+}
+
+/// Closes every socket and connection that is still open.
+pub(crate) fn close_all() {
+    for open in std::mem::take(&mut *OPEN_CONNECTIONS.lock().unwrap()) {
+        unsafe { csp_close(open.connection as *mut csp_conn_t) };
+    }
+    for socket in std::mem::take(&mut *OPEN_SOCKETS.lock().unwrap()) {
+        unsafe { csp_socket_close(socket as *mut csp_socket_t) };
+    }
+}
+
+/// Describes every connection in the LibCSP pool that is open, or still held by
+/// a `CspConnection`.
+pub(crate) fn connections() -> Vec<CspConnInfo> {
+    let open = OPEN_CONNECTIONS.lock().unwrap();
+    let table = debug::capture(|| unsafe { csp_conn_print_table() });
+
+    conn_table::pool_slots(&table)
+        .into_iter()
+        .filter_map(|connection| {
+            let holder = open
+                .iter()
+                .find(|open| open.connection == connection as usize)
+                .map(|open| (open.kind, open.opened.elapsed()));
+            // A slot that is closed and not held is free
+            if holder.is_none() && !unsafe { csp_conn_is_active(connection) } {
+                return None;
+            }
+            Some(unsafe { CspConnInfo::read(connection, holder) })
+        })
+        .collect()
+}
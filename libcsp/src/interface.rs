@@ -1,14 +1,99 @@
+use std::{
+    ffi::{CStr, CString},
+    ptr::NonNull,
+};
+
 use libcsp_sys::{
-    csp_iface_t, csp_zmqhub_init, csp_zmqhub_init_w_endpoints,
-    csp_zmqhub_init_w_name_endpoints_rxfilter,
+    csp_buffer_data_size, csp_iface_t, csp_iflist_get, csp_zmqhub_init,
+    csp_zmqhub_init_w_endpoints, csp_zmqhub_init_w_name_endpoints_rxfilter,
 };
 
-use crate::{csp_assert, utils::to_owned_c_str_ptr, CspError};
+use crate::{csp_assert, CspError, CspErrorKind};
+
+mod custom;
+pub use custom::*;
+mod lossy;
+pub use lossy::*;
+mod tcp;
+pub use tcp::*;
+mod zmq;
+pub use zmq::*;
+#[cfg(unix)]
+mod unix;
+#[cfg(unix)]
+pub use unix::*;
 
+#[cfg(feature = "socketcan")]
+mod can;
+#[cfg(feature = "socketcan")]
+pub use can::*;
+#[cfg(feature = "udp")]
+mod udp;
+#[cfg(feature = "udp")]
+pub use udp::*;
+#[cfg(feature = "usart")]
+mod usart;
+#[cfg(feature = "usart")]
+pub use usart::*;
+
+/// Creates a LibCSP interface, for `LibCspInstance::add_interface`.
+///
+/// The interface gets the node address, unless the builder was given its own
+/// with `address`, e.g. to appear as another node on that link.
 pub trait InterfaceBuilder {
     fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError>;
 }
 
+/// An interface that has been added to the global LibCSP instance.
+///
+/// LibCSP keeps interfaces for the lifetime of the process, so the handle
+/// can be copied freely and used for any number of routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CspInterfaceHandle {
+    iface: NonNull<csp_iface_t>,
+}
+
+// The interface is never freed, and LibCSP does its own locking around it.
+unsafe impl Send for CspInterfaceHandle {}
+unsafe impl Sync for CspInterfaceHandle {}
+
+impl CspInterfaceHandle {
+    pub(crate) fn from_ptr(iface: *mut csp_iface_t) -> Result<Self, CspError> {
+        NonNull::new(iface)
+            .map(|iface| Self { iface })
+            .ok_or_else(|| CspError {
+                kind: CspErrorKind::Driver,
+                message: "Interface driver didn't return an interface".to_string(),
+            })
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut csp_iface_t {
+        self.iface.as_ptr()
+    }
+
+    /// Finds an interface in the LibCSP interface list by name.
+    pub(crate) fn find(name: &str) -> Option<Self> {
+        let mut found = None;
+        for_each_iface(|iface| {
+            if found.is_none() && unsafe { CspInterfaceInfo::read(iface) }.name == name {
+                found = NonNull::new(iface).map(|iface| Self { iface });
+            }
+        });
+        found
+    }
+
+    /// A snapshot of the interface, including its packet counters.
+    pub fn info(&self) -> CspInterfaceInfo {
+        unsafe { CspInterfaceInfo::read(self.as_ptr()) }
+    }
+
+    pub fn name(&self) -> String {
+        self.info().name
+    }
+}
+
+/// A ZMQ hub interface with borrowed settings, see `CspZmqConfig` for a checked
+/// and owned alternative.
 pub enum CspZmqInterface<'a> {
     Basic {
         host: &'a str,
@@ -37,26 +122,37 @@ impl<'a> CspZmqInterface<'a> {
 
 impl InterfaceBuilder for CspZmqInterface<'_> {
     fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        // LibCSP copies the name and connects during the call, so the strings
+        // only need to live until it returns
+        let c_string = |s: &str| {
+            CString::new(s).map_err(|e| CspError {
+                kind: CspErrorKind::Inval,
+                message: format!("Invalid ZMQ interface string: {}", e),
+            })
+        };
+
         let mut return_interface = std::ptr::null_mut();
         unsafe {
             let result = match self {
-                CspZmqInterface::Basic { host, zmq_flags } => csp_zmqhub_init(
-                    address,
-                    to_owned_c_str_ptr(host),
-                    zmq_flags,
-                    &mut return_interface,
-                ),
+                CspZmqInterface::Basic { host, zmq_flags } => {
+                    let host = c_string(host)?;
+                    csp_zmqhub_init(address, host.as_ptr(), zmq_flags, &mut return_interface)
+                }
                 CspZmqInterface::WithEndpoints {
                     publish_endpoint,
                     subscribe_endpoint,
                     zmq_flags,
-                } => csp_zmqhub_init_w_endpoints(
-                    address,
-                    to_owned_c_str_ptr(publish_endpoint),
-                    to_owned_c_str_ptr(subscribe_endpoint),
-                    zmq_flags,
-                    &mut return_interface,
-                ),
+                } => {
+                    let publish_endpoint = c_string(publish_endpoint)?;
+                    let subscribe_endpoint = c_string(subscribe_endpoint)?;
+                    csp_zmqhub_init_w_endpoints(
+                        address,
+                        publish_endpoint.as_ptr(),
+                        subscribe_endpoint.as_ptr(),
+                        zmq_flags,
+                        &mut return_interface,
+                    )
+                }
                 CspZmqInterface::WithNameEndpointsFilter {
                     ifname,
                     addr,
@@ -64,16 +160,21 @@ impl InterfaceBuilder for CspZmqInterface<'_> {
                     publish_endpoint,
                     subscribe_endpoint,
                     zmq_flags,
-                } => csp_zmqhub_init_w_name_endpoints_rxfilter(
-                    to_owned_c_str_ptr(ifname),
-                    addr,
-                    rx_filter.as_ptr(),
-                    rx_filter.len() as u32,
-                    to_owned_c_str_ptr(publish_endpoint),
-                    to_owned_c_str_ptr(subscribe_endpoint),
-                    zmq_flags,
-                    &mut return_interface,
-                ),
+                } => {
+                    let ifname = c_string(ifname)?;
+                    let publish_endpoint = c_string(publish_endpoint)?;
+                    let subscribe_endpoint = c_string(subscribe_endpoint)?;
+                    csp_zmqhub_init_w_name_endpoints_rxfilter(
+                        ifname.as_ptr(),
+                        addr,
+                        rx_filter.as_ptr(),
+                        rx_filter.len() as u32,
+                        publish_endpoint.as_ptr(),
+                        subscribe_endpoint.as_ptr(),
+                        zmq_flags,
+                        &mut return_interface,
+                    )
+                }
             };
             csp_assert!(result, "Failed to initialize ZMQ interface");
         };
@@ -81,3 +182,104 @@ impl InterfaceBuilder for CspZmqInterface<'_> {
         Ok(return_interface)
     }
 }
+
+/// Packet counters of an interface, as kept by LibCSP.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CspInterfaceStats {
+    /// Successfully transmitted packets
+    pub tx: u32,
+    /// Successfully received packets
+    pub rx: u32,
+    /// Transmit errors
+    pub tx_error: u32,
+    /// Receive errors, e.g. too large message
+    pub rx_error: u32,
+    /// Dropped packets
+    pub drop: u32,
+    /// Authentication errors
+    pub autherr: u32,
+    /// Frame format errors
+    pub frame: u32,
+    /// Transmitted bytes
+    pub txbytes: u32,
+    /// Received bytes
+    pub rxbytes: u32,
+}
+
+/// A snapshot of an interface in the LibCSP interface list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspInterfaceInfo {
+    pub name: String,
+    pub address: u16,
+    pub netmask: u16,
+    /// Largest payload the interface carries. LibCSP v2.0 interfaces fragment packets
+    /// as needed, so this is the buffer data size.
+    pub mtu: u16,
+    pub is_default: bool,
+    pub stats: CspInterfaceStats,
+}
+
+impl CspInterfaceInfo {
+    unsafe fn read(iface: *const csp_iface_t) -> Self {
+        let iface = &*iface;
+        Self {
+            name: if iface.name.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(iface.name).to_string_lossy().into_owned()
+            },
+            address: iface.addr,
+            netmask: iface.netmask,
+            mtu: csp_buffer_data_size() as u16,
+            is_default: iface.is_default != 0,
+            stats: CspInterfaceStats {
+                tx: iface.tx,
+                rx: iface.rx,
+                tx_error: iface.tx_error,
+                rx_error: iface.rx_error,
+                drop: iface.drop,
+                autherr: iface.autherr,
+                frame: iface.frame,
+                txbytes: iface.txbytes,
+                rxbytes: iface.rxbytes,
+            },
+        }
+    }
+}
+
+/// Calls `f` for every interface in the LibCSP interface list.
+pub(crate) fn for_each_iface(mut f: impl FnMut(*mut csp_iface_t)) {
+    let mut iface = unsafe { csp_iflist_get() };
+    while !iface.is_null() {
+        f(iface);
+        iface = unsafe { (*iface).next };
+    }
+}
+
+pub(crate) fn interfaces() -> Vec<CspInterfaceInfo> {
+    let mut interfaces = Vec::new();
+    for_each_iface(|iface| interfaces.push(unsafe { CspInterfaceInfo::read(iface) }));
+    interfaces
+}
+
+/// Resets the counters of the interfaces for which `filter` returns true,
+/// and returns how many were reset.
+pub(crate) fn reset_counters(filter: impl Fn(&CspInterfaceInfo) -> bool) -> usize {
+    let mut reset = 0;
+    for_each_iface(|iface| unsafe {
+        if filter(&CspInterfaceInfo::read(iface)) {
+            let iface = &mut *iface;
+            iface.tx = 0;
+            iface.rx = 0;
+            iface.tx_error = 0;
+            iface.rx_error = 0;
+            iface.drop = 0;
+            iface.autherr = 0;
+            iface.frame = 0;
+            iface.txbytes = 0;
+            iface.rxbytes = 0;
+            reset += 1;
+        }
+    });
+    reset
+}
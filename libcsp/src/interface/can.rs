@@ -0,0 +1,88 @@
+use std::ffi::CString;
+
+use libcsp_sys::{csp_can_socketcan_open_and_add_interface, csp_iface_t};
+
+use crate::{csp_assert, CspError, CspErrorKind};
+
+use super::InterfaceBuilder;
+
+/// A CAN interface on a Linux SocketCAN device.
+///
+/// ```no_run
+/// use libcsp::interface::CspCanInterface;
+///
+/// let interface = CspCanInterface::new("can0").bitrate(1_000_000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspCanInterface {
+    device: String,
+    bitrate: i32,
+    promisc: bool,
+    ifname: String,
+    address: Option<u16>,
+}
+
+impl CspCanInterface {
+    pub fn new(device: impl Into<String>) -> Self {
+        Self {
+            device: device.into(),
+            bitrate: 0,
+            promisc: false,
+            ifname: "CAN".to_string(),
+            address: None,
+        }
+    }
+
+    /// Sets the bitrate of the device, which needs `CAP_NET_ADMIN`.
+    /// By default the bitrate is left as configured on the system.
+    pub fn bitrate(self, bitrate: i32) -> Self {
+        Self { bitrate, ..self }
+    }
+
+    /// Receives every frame on the bus, not only those addressed to this node.
+    pub fn promisc(self, promisc: bool) -> Self {
+        Self { promisc, ..self }
+    }
+
+    /// Sets the interface name, `CAN` by default.
+    pub fn ifname(self, ifname: impl Into<String>) -> Self {
+        Self {
+            ifname: ifname.into(),
+            ..self
+        }
+    }
+
+    pub fn address(self, address: u16) -> Self {
+        Self {
+            address: Some(address),
+            ..self
+        }
+    }
+}
+
+impl InterfaceBuilder for CspCanInterface {
+    fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        let invalid = |e: std::ffi::NulError| CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("Invalid CAN interface string: {}", e),
+        };
+        // Only borrowed by the call: the socket is opened on `device`, and the name is copied
+        let device = CString::new(self.device).map_err(invalid)?;
+        let ifname = CString::new(self.ifname).map_err(invalid)?;
+
+        let mut return_interface = std::ptr::null_mut();
+        unsafe {
+            let result = csp_can_socketcan_open_and_add_interface(
+                device.as_ptr(),
+                ifname.as_ptr(),
+                self.address.unwrap_or(address) as _,
+                self.bitrate,
+                self.promisc,
+                &mut return_interface,
+            );
+            csp_assert!(result, "Failed to initialize SocketCAN interface");
+        }
+
+        Ok(return_interface)
+    }
+}
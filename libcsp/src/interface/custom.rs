@@ -0,0 +1,240 @@
+use std::{
+    ffi::CString,
+    os::raw::{c_int, c_void},
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Mutex,
+};
+
+use libcsp_sys::{
+    csp_buffer_data_size, csp_buffer_free, csp_buffer_get, csp_id_get_header_size, csp_id_prepend,
+    csp_id_setup_rx, csp_id_strip, csp_iface_t, csp_iflist_add, csp_packet_t, csp_qfifo_write,
+    CSP_ERR_NONE, CSP_ERR_TX,
+};
+
+use crate::{csp_assert, CspError, CspErrorKind, CspId};
+
+use super::{CspInterfaceHandle, InterfaceBuilder};
+
+/// A CSP interface written in Rust, e.g. a test double or a modem with its own protocol.
+///
+/// Outgoing packets are handed to `transmit`, and received packets are passed to
+/// the `CspInterfaceRx` given to `start`.
+pub trait CspInterface: Send + 'static {
+    /// Called once when the interface is added, before any packet is transmitted.
+    fn start(&mut self, _rx: CspInterfaceRx) {}
+
+    /// Sends one packet towards `via`, the next hop address.
+    ///
+    /// Called from whichever thread sends or routes the packet.
+    fn transmit(&mut self, via: u16, frame: CspFrame<'_>) -> Result<(), CspError>;
+}
+
+/// An outgoing packet, as given to `CspInterface::transmit`.
+#[derive(Debug, Clone, Copy)]
+pub struct CspFrame<'a> {
+    id: CspId,
+    data: &'a [u8],
+    bytes: &'a [u8],
+}
+
+impl<'a> CspFrame<'a> {
+    pub fn id(&self) -> CspId {
+        self.id
+    }
+
+    /// The packet payload, without the CSP header.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The packet as it goes on the wire, the CSP header followed by the payload.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+/// Injects packets received by a `CspInterface` into the router.
+#[derive(Debug, Clone, Copy)]
+pub struct CspInterfaceRx {
+    iface: CspInterfaceHandle,
+}
+
+impl CspInterfaceRx {
+    /// The interface the packets are received on.
+    pub fn interface(&self) -> CspInterfaceHandle {
+        self.iface
+    }
+
+    /// Receives a packet given as its header and payload.
+    pub fn receive(&self, id: CspId, data: &[u8]) -> Result<(), CspError> {
+        let packet = self.packet(data.len())?;
+        unsafe {
+            (*packet).id = id.into();
+            (*packet).length = data.len() as u16;
+            std::ptr::copy_nonoverlapping(
+                data.as_ptr(),
+                (*packet).__bindgen_anon_2.data.as_mut_ptr(),
+                data.len(),
+            );
+            csp_qfifo_write(packet, self.iface.as_ptr(), std::ptr::null_mut());
+        }
+        Ok(())
+    }
+
+    /// Receives a packet as it came off the wire, like `CspFrame::as_bytes`.
+    pub fn receive_frame(&self, bytes: &[u8]) -> Result<(), CspError> {
+        let header_size = unsafe { csp_id_get_header_size() } as usize;
+        let packet = self.packet(bytes.len().saturating_sub(header_size))?;
+        unsafe {
+            csp_id_setup_rx(packet);
+            let frame = &mut (*packet).__bindgen_anon_1.__bindgen_anon_2;
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), frame.frame_begin, bytes.len());
+            frame.frame_length = bytes.len() as u16;
+
+            if csp_id_strip(packet) < 0 {
+                (*self.iface.as_ptr()).frame += 1;
+                csp_buffer_free(packet as *mut c_void);
+                return Err(CspError {
+                    kind: CspErrorKind::Inval,
+                    message: format!("Frame of {} bytes has no valid CSP header", bytes.len()),
+                });
+            }
+            csp_qfifo_write(packet, self.iface.as_ptr(), std::ptr::null_mut());
+        }
+        Ok(())
+    }
+
+    /// Gets a buffer for `length` bytes of payload.
+    fn packet(&self, length: usize) -> Result<*mut csp_packet_t, CspError> {
+        let iface = self.iface.as_ptr();
+        if length > unsafe { csp_buffer_data_size() } {
+            unsafe { (*iface).rx_error += 1 };
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: format!("Packet of {} bytes is larger than a buffer", length),
+            });
+        }
+
+        let packet = unsafe { csp_buffer_get(length) };
+        if packet.is_null() {
+            unsafe { (*iface).drop += 1 };
+            return Err(CspError {
+                kind: CspErrorKind::NoBuffersAvailable,
+                message: "No buffers available for received packet".to_string(),
+            });
+        }
+        Ok(packet)
+    }
+}
+
+/// Adds a `CspInterface` to LibCSP.
+///
+/// ```no_run
+/// use libcsp::interface::{CspFrame, CspInterface, CspRustInterface};
+/// use libcsp::CspError;
+///
+/// struct Discard;
+///
+/// impl CspInterface for Discard {
+///     fn transmit(&mut self, _via: u16, _frame: CspFrame<'_>) -> Result<(), CspError> {
+///         Ok(())
+///     }
+/// }
+///
+/// let interface = CspRustInterface::new("DISCARD", Discard);
+/// ```
+pub struct CspRustInterface<I> {
+    ifname: String,
+    address: Option<u16>,
+    interface: I,
+}
+
+impl<I: CspInterface> CspRustInterface<I> {
+    pub fn new(ifname: impl Into<String>, interface: I) -> Self {
+        Self {
+            ifname: ifname.into(),
+            address: None,
+            interface,
+        }
+    }
+
+    pub fn address(self, address: u16) -> Self {
+        Self {
+            address: Some(address),
+            ..self
+        }
+    }
+}
+
+impl<I: CspInterface> InterfaceBuilder for CspRustInterface<I> {
+    fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        let ifname = CString::new(self.ifname).map_err(|e| CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("Invalid interface name: {}", e),
+        })?;
+
+        // LibCSP never removes interfaces, so the interface and its name are leaked.
+        // The Rust side lives in `interface_data` for as long.
+        let iface: &mut csp_iface_t = Box::leak(Box::new(unsafe { std::mem::zeroed() }));
+        iface.name = ifname.into_raw();
+        iface.addr = self.address.unwrap_or(address);
+        iface.nexthop = Some(nexthop::<I>);
+
+        let rx = CspInterfaceRx {
+            iface: CspInterfaceHandle::from_ptr(iface)?,
+        };
+        let interface: &Mutex<I> = Box::leak(Box::new(Mutex::new(self.interface)));
+        iface.interface_data = interface as *const Mutex<I> as *mut c_void;
+
+        unsafe {
+            let result = csp_iflist_add(iface);
+            csp_assert!(result, "Failed to add Rust interface");
+        }
+
+        // Started only once LibCSP has the interface. The lock holds back any
+        // transmit until `start` returns.
+        interface.lock().unwrap().start(rx);
+
+        Ok(iface)
+    }
+}
+
+/// Hands an outgoing packet to the `CspInterface`. The packet is freed in every case.
+unsafe extern "C" fn nexthop<I: CspInterface>(
+    iface: *mut csp_iface_t,
+    via: u16,
+    packet: *mut csp_packet_t,
+    _from_me: c_int,
+) -> c_int {
+    let interface = &*((*iface).interface_data as *const Mutex<I>);
+
+    csp_id_prepend(packet);
+    let frame = &(*packet).__bindgen_anon_1.__bindgen_anon_2;
+    let frame = CspFrame {
+        id: (*packet).id.into(),
+        data: std::slice::from_raw_parts(
+            (*packet).__bindgen_anon_2.data.as_ptr(),
+            (*packet).length as usize,
+        ),
+        bytes: std::slice::from_raw_parts(frame.frame_begin, frame.frame_length as usize),
+    };
+
+    // Unwinding into C is undefined, so a panic counts as a failed transmit
+    let result = catch_unwind(AssertUnwindSafe(|| match interface.lock() {
+        Ok(mut interface) => interface.transmit(via, frame),
+        Err(_) => Err(CspError {
+            kind: CspErrorKind::Driver,
+            message: "Interface panicked earlier".to_string(),
+        }),
+    }));
+    csp_buffer_free(packet as *mut c_void);
+
+    match result {
+        Ok(Ok(())) => CSP_ERR_NONE as c_int,
+        Ok(Err(error)) => {
+            log::warn!(target: "libcsp", "Interface transmit failed: {:?}", error);
+            CSP_ERR_TX as c_int
+        }
+        Err(_) => CSP_ERR_TX as c_int,
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::CspError;
+
+use super::{CspFrame, CspInterface, CspInterfaceRx, CspRustInterface};
+
+/// Impairments of a `CspLossyLink`, in both directions. The default is a perfect link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CspLossyLinkConfig {
+    drop_probability: f64,
+    duplicate_probability: f64,
+    reorder_probability: f64,
+    latency: Duration,
+    jitter: Duration,
+    bandwidth: Option<u64>,
+    seed: u64,
+}
+
+impl Default for CspLossyLinkConfig {
+    fn default() -> Self {
+        Self {
+            drop_probability: 0.0,
+            duplicate_probability: 0.0,
+            reorder_probability: 0.0,
+            latency: Duration::ZERO,
+            jitter: Duration::ZERO,
+            bandwidth: None,
+            seed: 0,
+        }
+    }
+}
+
+impl CspLossyLinkConfig {
+    /// Chance that a packet is lost.
+    ///
+    /// # Panics
+    ///
+    /// If `probability` is not between 0 and 1.
+    pub fn drop_probability(self, probability: f64) -> Self {
+        Self {
+            drop_probability: check_probability(probability),
+            ..self
+        }
+    }
+
+    /// Chance that a packet arrives twice.
+    ///
+    /// # Panics
+    ///
+    /// If `probability` is not between 0 and 1.
+    pub fn duplicate_probability(self, probability: f64) -> Self {
+        Self {
+            duplicate_probability: check_probability(probability),
+            ..self
+        }
+    }
+
+    /// Chance that a packet skips the latency, and so overtakes the packets before it.
+    ///
+    /// # Panics
+    ///
+    /// If `probability` is not between 0 and 1.
+    pub fn reorder_probability(self, probability: f64) -> Self {
+        Self {
+            reorder_probability: check_probability(probability),
+            ..self
+        }
+    }
+
+    /// Fixed delay of every packet.
+    pub fn latency(self, latency: Duration) -> Self {
+        Self { latency, ..self }
+    }
+
+    /// Random delay of up to `jitter` on top of, or below, the latency.
+    pub fn jitter(self, jitter: Duration) -> Self {
+        Self { jitter, ..self }
+    }
+
+    /// Caps each direction at `bytes_per_second`, later packets queue behind earlier ones.
+    ///
+    /// # Panics
+    ///
+    /// If `bytes_per_second` is zero.
+    pub fn bandwidth(self, bytes_per_second: u64) -> Self {
+        assert!(bytes_per_second > 0, "Bandwidth must be larger than zero");
+        Self {
+            bandwidth: Some(bytes_per_second),
+            ..self
+        }
+    }
+
+    /// Seeds the random number generator, the same seed and traffic give the same run.
+    pub fn seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+fn check_probability(probability: f64) -> f64 {
+    assert!(
+        (0.0..=1.0).contains(&probability),
+        "Probability {} is not between 0 and 1",
+        probability
+    );
+    probability
+}
+
+/// What a `CspLossyLink` did with the packets sent over it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CspLossyLinkStats {
+    /// Packets transmitted on either end
+    pub sent: u64,
+    pub dropped: u64,
+    pub duplicated: u64,
+    pub reordered: u64,
+    /// Packets handed to the receiving end, duplicates included
+    pub delivered: u64,
+}
+
+/// A simulated link between two interfaces in this process, e.g. a bad space link.
+///
+/// Packets transmitted on one end are received on the other. As only one LibCSP
+/// instance can run per process, the far end usually holds the address of the
+/// remote node, so that packets to it are delivered back to this node.
+///
+/// ```no_run
+/// use libcsp::interface::{CspLossyLink, CspLossyLinkConfig};
+/// use libcsp::{LibCspBuilder, LibCspConfig, Route};
+/// use std::time::Duration;
+///
+/// let csp = LibCspBuilder::new(LibCspConfig::new(1)).build();
+/// let link = CspLossyLink::new(
+///     CspLossyLinkConfig::default()
+///         .drop_probability(0.1)
+///         .latency(Duration::from_millis(20))
+///         .seed(42),
+/// );
+/// csp.add_interface_route(Route::new(2), link.end_a("SAT")).unwrap();
+/// csp.add_interface(link.end_b("GROUND").address(2)).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct CspLossyLink {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    config: CspLossyLinkConfig,
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+struct State {
+    rng: SplitMix64,
+    rx: [Option<CspInterfaceRx>; 2],
+    /// When each direction has finished sending its queued packets
+    busy_until: [Instant; 2],
+    queue: BinaryHeap<Reverse<Scheduled>>,
+    sequence: u64,
+    stats: CspLossyLinkStats,
+    delivering: bool,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Scheduled {
+    at: Instant,
+    sequence: u64,
+    to: usize,
+    bytes: Vec<u8>,
+}
+
+impl CspLossyLink {
+    pub fn new(config: CspLossyLinkConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    rng: SplitMix64(config.seed),
+                    rx: [None, None],
+                    busy_until: [now, now],
+                    queue: BinaryHeap::new(),
+                    sequence: 0,
+                    stats: CspLossyLinkStats::default(),
+                    delivering: false,
+                }),
+                config,
+                wake: Condvar::new(),
+            }),
+        }
+    }
+
+    /// The first end of the link, as an interface named `ifname`.
+    pub fn end_a(&self, ifname: impl Into<String>) -> CspRustInterface<CspLossyLinkEnd> {
+        self.end(ifname, 0)
+    }
+
+    /// The second end of the link, as an interface named `ifname`.
+    pub fn end_b(&self, ifname: impl Into<String>) -> CspRustInterface<CspLossyLinkEnd> {
+        self.end(ifname, 1)
+    }
+
+    fn end(&self, ifname: impl Into<String>, end: usize) -> CspRustInterface<CspLossyLinkEnd> {
+        CspRustInterface::new(
+            ifname,
+            CspLossyLinkEnd {
+                shared: self.shared.clone(),
+                end,
+            },
+        )
+    }
+
+    pub fn stats(&self) -> CspLossyLinkStats {
+        self.shared.state.lock().unwrap().stats
+    }
+}
+
+/// One end of a `CspLossyLink`.
+pub struct CspLossyLinkEnd {
+    shared: Arc<Shared>,
+    end: usize,
+}
+
+impl CspInterface for CspLossyLinkEnd {
+    fn start(&mut self, rx: CspInterfaceRx) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.rx[self.end] = Some(rx);
+
+        if !state.delivering {
+            state.delivering = true;
+            let shared = self.shared.clone();
+            thread::Builder::new()
+                .name("csp-lossy-link".to_string())
+                .spawn(move || shared.deliver())
+                .expect("Failed to spawn lossy link thread");
+        }
+    }
+
+    fn transmit(&mut self, _via: u16, frame: CspFrame<'_>) -> Result<(), CspError> {
+        let config = &self.shared.config;
+        let mut state = self.shared.state.lock().unwrap();
+        state.stats.sent += 1;
+
+        // The packet takes up the link even if it is lost on the way
+        let now = Instant::now();
+        let sent_at = match config.bandwidth {
+            Some(bytes_per_second) => {
+                let start = state.busy_until[self.end].max(now);
+                let duration = Duration::from_secs_f64(
+                    frame.as_bytes().len() as f64 / bytes_per_second as f64,
+                );
+                state.busy_until[self.end] = start + duration;
+                start + duration
+            }
+            None => now,
+        };
+
+        if state.rng.chance(config.drop_probability) {
+            state.stats.dropped += 1;
+            return Ok(());
+        }
+
+        let copies = if state.rng.chance(config.duplicate_probability) {
+            state.stats.duplicated += 1;
+            2
+        } else {
+            1
+        };
+        for _ in 0..copies {
+            let delay = if state.rng.chance(config.reorder_probability) {
+                state.stats.reordered += 1;
+                Duration::ZERO
+            } else {
+                // Signed, as the jitter can take the delay below the latency
+                let jitter = config.jitter.as_secs_f64() * (2.0 * state.rng.next_f64() - 1.0);
+                let latency = config.latency.as_secs_f64() + jitter;
+                Duration::from_secs_f64(latency.max(0.0))
+            };
+
+            let sequence = state.sequence;
+            state.sequence += 1;
+            state.queue.push(Reverse(Scheduled {
+                at: sent_at + delay,
+                sequence,
+                to: 1 - self.end,
+                bytes: frame.as_bytes().to_vec(),
+            }));
+        }
+
+        self.shared.wake.notify_one();
+        Ok(())
+    }
+}
+
+impl Shared {
+    /// Hands every packet to the receiving end once its time has come, for as long as the process runs.
+    fn deliver(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            match state.queue.peek() {
+                Some(Reverse(next)) if next.at <= now => {
+                    let Reverse(next) = state.queue.pop().unwrap();
+                    let rx = state.rx[next.to];
+                    if rx.is_some() {
+                        state.stats.delivered += 1;
+                    }
+
+                    drop(state);
+                    if let Some(rx) = rx {
+                        // Bad frames are counted on the interface
+                        let _ = rx.receive_frame(&next.bytes);
+                    }
+                    state = self.state.lock().unwrap();
+                }
+                Some(Reverse(next)) => {
+                    let timeout = next.at - now;
+                    state = self.wake.wait_timeout(state, timeout).unwrap().0;
+                }
+                None => state = self.wake.wait(state).unwrap(),
+            }
+        }
+    }
+}
+
+/// A small, seedable random number generator, see <https://prng.di.unimi.it/splitmix64.c>.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+}
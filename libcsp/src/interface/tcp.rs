@@ -0,0 +1,224 @@
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use libcsp_sys::csp_iface_t;
+
+use crate::{CspError, CspErrorKind};
+
+use super::{CspFrame, CspInterface, CspInterfaceRx, CspRustInterface, InterfaceBuilder};
+
+/// CSP packets over a TCP stream, e.g. to a remote modem gateway.
+///
+/// Every packet is sent as a 2 byte big-endian length, followed by the CSP header
+/// and the payload. When the stream drops, the client connects again and the
+/// listener accepts the next peer. Packets sent while there is no peer are dropped.
+///
+/// ```no_run
+/// use libcsp::interface::CspTcpInterface;
+///
+/// let interface = CspTcpInterface::connect("gateway.local:5000").ifname("GATEWAY");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspTcpInterface {
+    mode: TcpMode,
+    socket_address: String,
+    ifname: String,
+    address: Option<u16>,
+    reconnect_interval: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TcpMode {
+    Connect,
+    Listen,
+}
+
+impl CspTcpInterface {
+    /// Connects to a listening peer at `socket_address`, like `host:port`.
+    pub fn connect(socket_address: impl Into<String>) -> Self {
+        Self::new(TcpMode::Connect, socket_address.into())
+    }
+
+    /// Listens on `socket_address`, like `0.0.0.0:port`, for one peer at a time.
+    pub fn listen(socket_address: impl Into<String>) -> Self {
+        Self::new(TcpMode::Listen, socket_address.into())
+    }
+
+    fn new(mode: TcpMode, socket_address: String) -> Self {
+        Self {
+            mode,
+            socket_address,
+            ifname: "TCP".to_string(),
+            address: None,
+            reconnect_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the interface name, `TCP` by default.
+    pub fn ifname(self, ifname: impl Into<String>) -> Self {
+        Self {
+            ifname: ifname.into(),
+            ..self
+        }
+    }
+
+    pub fn address(self, address: u16) -> Self {
+        Self {
+            address: Some(address),
+            ..self
+        }
+    }
+
+    /// How long the client waits between connection attempts, 1 second by default.
+    pub fn reconnect_interval(self, reconnect_interval: Duration) -> Self {
+        Self {
+            reconnect_interval,
+            ..self
+        }
+    }
+}
+
+impl InterfaceBuilder for CspTcpInterface {
+    fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        let error = |kind, e: io::Error| CspError {
+            kind,
+            message: format!("TCP interface `{}`: {}", self.socket_address, e),
+        };
+
+        // Bad addresses and ports in use are reported now, lost peers only later
+        let listener = match self.mode {
+            TcpMode::Listen => Some(
+                TcpListener::bind(&self.socket_address)
+                    .map_err(|e| error(CspErrorKind::Driver, e))?,
+            ),
+            TcpMode::Connect => {
+                self.socket_address
+                    .to_socket_addrs()
+                    .map_err(|e| error(CspErrorKind::Inval, e))?;
+                None
+            }
+        };
+
+        let link = TcpLink {
+            socket_address: self.socket_address.clone(),
+            peers: Some(TcpPeers {
+                socket_address: self.socket_address,
+                listener,
+                reconnect_interval: self.reconnect_interval,
+            }),
+            stream: Arc::new(Mutex::new(None)),
+        };
+        let mut interface = CspRustInterface::new(self.ifname, link);
+        if let Some(address) = self.address {
+            interface = interface.address(address);
+        }
+        interface.build(address)
+    }
+}
+
+struct TcpLink {
+    socket_address: String,
+    /// Moved to the receiving thread on start
+    peers: Option<TcpPeers>,
+    /// The stream to the current peer, if any
+    stream: Arc<Mutex<Option<TcpStream>>>,
+}
+
+/// Where the peers come from.
+struct TcpPeers {
+    socket_address: String,
+    listener: Option<TcpListener>,
+    reconnect_interval: Duration,
+}
+
+impl TcpPeers {
+    /// Waits for the next peer.
+    fn next(&self) -> TcpStream {
+        loop {
+            let stream = match &self.listener {
+                Some(listener) => listener.accept().map(|(stream, _)| stream),
+                None => TcpStream::connect(&self.socket_address),
+            };
+            match stream {
+                Ok(stream) => {
+                    let _ = stream.set_nodelay(true);
+                    return stream;
+                }
+                Err(e) => {
+                    log::debug!(target: "libcsp", "TCP interface `{}`: {}", self.socket_address, e);
+                    thread::sleep(self.reconnect_interval);
+                }
+            }
+        }
+    }
+
+    /// Receives packets from one peer, until it drops.
+    fn receive(mut stream: TcpStream, rx: &CspInterfaceRx) -> io::Result<()> {
+        let mut length = [0; 2];
+        let mut frame = Vec::new();
+        loop {
+            stream.read_exact(&mut length)?;
+            frame.resize(u16::from_be_bytes(length) as usize, 0);
+            stream.read_exact(&mut frame)?;
+            // Bad frames are counted on the interface, and the stream stays in sync
+            let _ = rx.receive_frame(&frame);
+        }
+    }
+}
+
+impl CspInterface for TcpLink {
+    fn start(&mut self, rx: CspInterfaceRx) {
+        let peers = self.peers.take().expect("TCP interface started twice");
+        let current = self.stream.clone();
+
+        thread::Builder::new()
+            .name("csp-tcp".to_string())
+            .spawn(move || loop {
+                let stream = peers.next();
+                match stream.try_clone() {
+                    Ok(writer) => *current.lock().unwrap() = Some(writer),
+                    Err(_) => continue,
+                }
+
+                if let Err(e) = TcpPeers::receive(stream, &rx) {
+                    log::info!(target: "libcsp", "TCP interface `{}` lost its peer: {}", peers.socket_address, e);
+                }
+                if let Some(stream) = current.lock().unwrap().take() {
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+                if peers.listener.is_none() {
+                    thread::sleep(peers.reconnect_interval);
+                }
+            })
+            .expect("Failed to spawn TCP interface thread");
+    }
+
+    fn transmit(&mut self, _via: u16, frame: CspFrame<'_>) -> Result<(), CspError> {
+        let bytes = frame.as_bytes();
+        let mut message = Vec::with_capacity(2 + bytes.len());
+        message.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        message.extend_from_slice(bytes);
+
+        let mut stream = self.stream.lock().unwrap();
+        let Some(writer) = stream.as_mut() else {
+            return Err(CspError {
+                kind: CspErrorKind::Tx,
+                message: format!("TCP interface `{}` is not connected", self.socket_address),
+            });
+        };
+
+        writer.write_all(&message).map_err(|e| {
+            // Also stops the receiving side, which then waits for the next peer
+            let _ = writer.shutdown(Shutdown::Both);
+            CspError {
+                kind: CspErrorKind::Tx,
+                message: format!("TCP interface `{}`: {}", self.socket_address, e),
+            }
+        })
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    ffi::CString,
+    net::{Ipv4Addr, UdpSocket},
+};
+
+use libcsp_sys::{csp_if_udp_conf_t, csp_if_udp_init, csp_iface_t};
+
+use crate::{CspError, CspErrorKind};
+
+use super::InterfaceBuilder;
+
+/// A point-to-point interface that sends every packet as one UDP datagram to
+/// a single peer, and receives on a local port.
+///
+/// The LibCSP driver binds the local port on its own thread and can't report a
+/// failure, so the port is checked when the interface is added. A port taken by
+/// another program in the moment between that check and the bind leaves the
+/// interface receiving nothing.
+///
+/// ```no_run
+/// use libcsp::interface::CspUdpInterface;
+///
+/// let interface = CspUdpInterface::new("192.168.1.20", 9600, 9600).ifname("GROUND");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspUdpInterface {
+    host: String,
+    local_port: u16,
+    remote_port: u16,
+    ifname: String,
+    address: Option<u16>,
+}
+
+impl CspUdpInterface {
+    /// # Arguments
+    ///
+    /// * `host` - IPv4 address of the peer, host names are not resolved
+    /// * `local_port` - UDP port this node receives on
+    /// * `remote_port` - UDP port the peer receives on
+    pub fn new(host: impl Into<String>, local_port: u16, remote_port: u16) -> Self {
+        Self {
+            host: host.into(),
+            local_port,
+            remote_port,
+            ifname: "UDP".to_string(),
+            address: None,
+        }
+    }
+
+    /// Sets the interface name, `UDP` by default.
+    pub fn ifname(self, ifname: impl Into<String>) -> Self {
+        Self {
+            ifname: ifname.into(),
+            ..self
+        }
+    }
+
+    pub fn address(self, address: u16) -> Self {
+        Self {
+            address: Some(address),
+            ..self
+        }
+    }
+}
+
+impl InterfaceBuilder for CspUdpInterface {
+    fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        // LibCSP only logs a bad peer address and then sends nowhere
+        if self.host.parse::<Ipv4Addr>().is_err() {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: format!("UDP host `{}` is not an IPv4 address", self.host),
+            });
+        }
+        let ifname = CString::new(self.ifname).map_err(|e| CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("Invalid UDP interface name: {}", e),
+        })?;
+        let host = CString::new(self.host).unwrap();
+
+        // Released again straight away, for the driver to bind
+        UdpSocket::bind((Ipv4Addr::UNSPECIFIED, self.local_port)).map_err(|e| CspError {
+            kind: CspErrorKind::Driver,
+            message: format!("UDP port {} can't be bound: {}", self.local_port, e),
+        })?;
+
+        // The driver keeps pointers to the interface, its configuration and both
+        // strings, and LibCSP never removes interfaces, so all of them are leaked.
+        unsafe {
+            let conf: &mut csp_if_udp_conf_t = Box::leak(Box::new(std::mem::zeroed()));
+            conf.host = host.into_raw();
+            conf.lport = self.local_port.into();
+            conf.rport = self.remote_port.into();
+
+            let iface: &mut csp_iface_t = Box::leak(Box::new(std::mem::zeroed()));
+            iface.name = ifname.into_raw();
+            iface.addr = self.address.unwrap_or(address);
+
+            csp_if_udp_init(iface, conf);
+            Ok(iface)
+        }
+    }
+}
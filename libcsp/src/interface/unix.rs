@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    io,
+    os::unix::net::UnixDatagram,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use libcsp_sys::csp_iface_t;
+
+use crate::{CspError, CspErrorKind};
+
+use super::{CspFrame, CspInterface, CspInterfaceRx, CspRustInterface, InterfaceBuilder};
+
+/// Largest datagram, comfortably above a CSP header and a buffer of data.
+const MAX_DATAGRAM: usize = 4096;
+
+/// CSP packets over Unix datagram sockets, for processes on the same host.
+///
+/// Every datagram is the 2 byte big-endian next hop address, followed by the CSP
+/// header and the payload. With `connect`, all processes that join the same
+/// `CspUnixBroker` form a subnet, and the broker passes each packet to the process
+/// holding its next hop address.
+///
+/// ```no_run
+/// use libcsp::interface::CspUnixInterface;
+///
+/// let interface = CspUnixInterface::connect("/run/csp/broker.sock");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspUnixInterface {
+    remote_path: PathBuf,
+    local_path: Option<PathBuf>,
+    broker: bool,
+    ifname: String,
+    address: Option<u16>,
+}
+
+impl CspUnixInterface {
+    /// Joins the subnet of the broker listening on `broker_path`, which must be running.
+    pub fn connect(broker_path: impl AsRef<Path>) -> Self {
+        Self {
+            remote_path: broker_path.as_ref().to_path_buf(),
+            local_path: None,
+            broker: true,
+            ifname: "UNIX".to_string(),
+            address: None,
+        }
+    }
+
+    /// Links directly to a single process, which receives on `remote_path`.
+    pub fn peer(local_path: impl AsRef<Path>, remote_path: impl AsRef<Path>) -> Self {
+        Self {
+            remote_path: remote_path.as_ref().to_path_buf(),
+            local_path: Some(local_path.as_ref().to_path_buf()),
+            broker: false,
+            ifname: "UNIX".to_string(),
+            address: None,
+        }
+    }
+
+    /// Sets the path this interface receives on. With a broker, it defaults to
+    /// the broker path followed by the address, like `broker.sock.12`.
+    pub fn local_path(self, local_path: impl AsRef<Path>) -> Self {
+        Self {
+            local_path: Some(local_path.as_ref().to_path_buf()),
+            ..self
+        }
+    }
+
+    /// Sets the interface name, `UNIX` by default.
+    pub fn ifname(self, ifname: impl Into<String>) -> Self {
+        Self {
+            ifname: ifname.into(),
+            ..self
+        }
+    }
+
+    pub fn address(self, address: u16) -> Self {
+        Self {
+            address: Some(address),
+            ..self
+        }
+    }
+}
+
+impl InterfaceBuilder for CspUnixInterface {
+    fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        let address = self.address.unwrap_or(address);
+        let local_path = self.local_path.unwrap_or_else(|| {
+            let mut path = self.remote_path.clone().into_os_string();
+            path.push(format!(".{}", address));
+            path.into()
+        });
+        let error = |e: io::Error| CspError {
+            kind: CspErrorKind::Driver,
+            message: format!("Unix interface `{}`: {}", local_path.display(), e),
+        };
+
+        let socket = bind(&local_path).map_err(error)?;
+        if self.broker {
+            // The broker learns where to find this address from any datagram
+            socket
+                .send_to(&address.to_be_bytes(), &self.remote_path)
+                .map_err(error)?;
+        }
+
+        let link = UnixLink {
+            socket,
+            remote_path: self.remote_path,
+        };
+        CspRustInterface::new(self.ifname, link)
+            .address(address)
+            .build(address)
+    }
+}
+
+/// Binds a datagram socket, replacing a socket file left behind by an earlier run.
+fn bind(path: &Path) -> io::Result<UnixDatagram> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    UnixDatagram::bind(path)
+}
+
+struct UnixLink {
+    socket: UnixDatagram,
+    remote_path: PathBuf,
+}
+
+impl CspInterface for UnixLink {
+    fn start(&mut self, rx: CspInterfaceRx) {
+        let socket = self
+            .socket
+            .try_clone()
+            .expect("Failed to clone Unix socket");
+
+        thread::Builder::new()
+            .name("csp-unix".to_string())
+            .spawn(move || {
+                let mut datagram = [0; MAX_DATAGRAM];
+                while let Ok(length) = socket.recv(&mut datagram) {
+                    if length > 2 {
+                        // Bad frames are counted on the interface
+                        let _ = rx.receive_frame(&datagram[2..length]);
+                    }
+                }
+            })
+            .expect("Failed to spawn Unix interface thread");
+    }
+
+    fn transmit(&mut self, via: u16, frame: CspFrame<'_>) -> Result<(), CspError> {
+        let mut datagram = Vec::with_capacity(2 + frame.as_bytes().len());
+        datagram.extend_from_slice(&via.to_be_bytes());
+        datagram.extend_from_slice(frame.as_bytes());
+
+        self.socket
+            .send_to(&datagram, &self.remote_path)
+            .map(|_| ())
+            .map_err(|e| CspError {
+                kind: CspErrorKind::Tx,
+                message: format!("Unix interface to `{}`: {}", self.remote_path.display(), e),
+            })
+    }
+}
+
+/// Passes packets between the processes that joined it with `CspUnixInterface::connect`.
+///
+/// The broker is not a CSP node itself, and needs no LibCSP instance.
+///
+/// ```no_run
+/// use libcsp::interface::CspUnixBroker;
+///
+/// CspUnixBroker::bind("/run/csp/broker.sock").unwrap().spawn();
+/// ```
+pub struct CspUnixBroker {
+    socket: UnixDatagram,
+    path: PathBuf,
+    /// Where each address receives
+    peers: HashMap<u16, PathBuf>,
+}
+
+impl CspUnixBroker {
+    pub fn bind(path: impl AsRef<Path>) -> Result<Self, CspError> {
+        let path = path.as_ref().to_path_buf();
+        let socket = bind(&path).map_err(|e| CspError {
+            kind: CspErrorKind::Driver,
+            message: format!("Unix broker `{}`: {}", path.display(), e),
+        })?;
+
+        Ok(Self {
+            socket,
+            path,
+            peers: HashMap::new(),
+        })
+    }
+
+    /// Runs the broker on its own thread, for as long as the process runs.
+    pub fn spawn(self) -> JoinHandle<()> {
+        thread::Builder::new()
+            .name("csp-unix-broker".to_string())
+            .spawn(move || {
+                if let Err(e) = self.run() {
+                    log::error!(target: "libcsp", "Unix broker stopped: {}", e);
+                }
+            })
+            .expect("Failed to spawn Unix broker thread")
+    }
+
+    /// Passes packets on, until the socket fails.
+    pub fn run(mut self) -> io::Result<()> {
+        let mut datagram = [0; MAX_DATAGRAM];
+        loop {
+            let (length, sender) = self.socket.recv_from(&mut datagram)?;
+            if length < 2 {
+                continue;
+            }
+            let via = u16::from_be_bytes([datagram[0], datagram[1]]);
+
+            // A bare address joins the subnet, anything else is a packet for `via`
+            if length == 2 {
+                if let Some(path) = sender.as_pathname() {
+                    log::debug!(target: "libcsp", "Unix broker: {} joined at `{}`", via, path.display());
+                    self.peers.insert(via, path.to_path_buf());
+                }
+                continue;
+            }
+
+            let Some(path) = self.peers.get(&via) else {
+                log::debug!(target: "libcsp", "Unix broker: no process for address {}", via);
+                continue;
+            };
+            if let Err(e) = self.socket.send_to(&datagram[..length], path) {
+                log::info!(target: "libcsp", "Unix broker: {} left, {}", via, e);
+                self.peers.remove(&via);
+            }
+        }
+    }
+}
+
+impl Drop for CspUnixBroker {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
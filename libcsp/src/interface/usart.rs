@@ -0,0 +1,85 @@
+use std::ffi::CString;
+
+use libcsp_sys::{csp_iface_t, csp_usart_conf_t, csp_usart_open_and_add_kiss_interface};
+
+use crate::{csp_assert, CspError, CspErrorKind};
+
+use super::InterfaceBuilder;
+
+/// A KISS interface over a serial port, e.g. a radio modem.
+///
+/// The port is opened with 8 data bits, 1 stop bit and no parity.
+///
+/// ```no_run
+/// use libcsp::interface::CspUsartInterface;
+///
+/// let interface = CspUsartInterface::new("/dev/ttyUSB0", 115200).ifname("RADIO");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspUsartInterface {
+    device: String,
+    baudrate: u32,
+    ifname: String,
+    address: Option<u16>,
+}
+
+impl CspUsartInterface {
+    pub fn new(device: impl Into<String>, baudrate: u32) -> Self {
+        Self {
+            device: device.into(),
+            baudrate,
+            ifname: "KISS".to_string(),
+            address: None,
+        }
+    }
+
+    /// Sets the interface name, `KISS` by default.
+    pub fn ifname(self, ifname: impl Into<String>) -> Self {
+        Self {
+            ifname: ifname.into(),
+            ..self
+        }
+    }
+
+    pub fn address(self, address: u16) -> Self {
+        Self {
+            address: Some(address),
+            ..self
+        }
+    }
+}
+
+impl InterfaceBuilder for CspUsartInterface {
+    fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        let invalid = |e: std::ffi::NulError| CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("Invalid USART interface string: {}", e),
+        };
+        // `csp_usart_open_and_add_kiss_interface` keeps its own copy of the name,
+        // and has opened the device by the time it returns
+        let device = CString::new(self.device).map_err(invalid)?;
+        let ifname = CString::new(self.ifname).map_err(invalid)?;
+
+        let conf = csp_usart_conf_t {
+            device: device.as_ptr(),
+            baudrate: self.baudrate,
+            databits: 8,
+            stopbits: 1,
+            paritysetting: 0,
+            checkparity: 0,
+        };
+
+        let mut return_interface = std::ptr::null_mut();
+        unsafe {
+            let result = csp_usart_open_and_add_kiss_interface(
+                &conf,
+                ifname.as_ptr(),
+                self.address.unwrap_or(address),
+                &mut return_interface,
+            );
+            csp_assert!(result, "Failed to initialize USART interface");
+        }
+
+        Ok(return_interface)
+    }
+}
@@ -0,0 +1,261 @@
+use std::ffi::CString;
+
+use libcsp_sys::{
+    csp_iface_t, csp_zmqhub_init_w_name_endpoints_rxfilter, CSP_ZMQPROXY_PUBLISH_PORT,
+    CSP_ZMQPROXY_SUBSCRIBE_PORT,
+};
+
+use crate::{csp_assert, CspError, CspErrorKind};
+
+use super::InterfaceBuilder;
+
+/// The largest address in the CSP v2 header.
+const MAX_ADDRESS: u16 = 0x3fff;
+
+/// Transports understood by ZMQ.
+const ZMQ_TRANSPORTS: &[&str] = &[
+    "tcp", "ipc", "inproc", "pgm", "epgm", "tipc", "vmci", "ws", "wss",
+];
+
+/// A ZMQ hub interface, checked before it is handed to LibCSP.
+///
+/// ```no_run
+/// use libcsp::interface::CspZmqConfig;
+///
+/// let interface = CspZmqConfig::endpoints("tcp://localhost:6000", "tcp://localhost:7000")
+///     .ifname("HUB")
+///     .rx_filter([10, 11]);
+/// // Without an rx filter, every packet on the hub is received
+/// let sniffer = CspZmqConfig::host("localhost");
+/// ```
+///
+/// The `flags` argument of the LibCSP ZMQ driver is always 0, LibCSP v2.0 doesn't use it.
+///
+/// With the `serde` feature, it can be deserialized from either a `host` or both
+/// endpoints, with the optional `ifname`, `address` and `rx_filter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(try_from = "ZmqConfigFile")
+)]
+pub struct CspZmqConfig {
+    publish_endpoint: String,
+    subscribe_endpoint: String,
+    ifname: String,
+    address: Option<u16>,
+    rx_filter: Vec<u16>,
+}
+
+impl CspZmqConfig {
+    /// Connects to a `zmqproxy` on `host`, with the default ports.
+    pub fn host(host: &str) -> Self {
+        // The proxy subscribes on one port, and publishes on the other
+        Self::endpoints(
+            format!("tcp://{}:{}", host, CSP_ZMQPROXY_SUBSCRIBE_PORT),
+            format!("tcp://{}:{}", host, CSP_ZMQPROXY_PUBLISH_PORT),
+        )
+    }
+
+    /// Publishes packets to `publish_endpoint`, and subscribes to `subscribe_endpoint`.
+    pub fn endpoints(
+        publish_endpoint: impl Into<String>,
+        subscribe_endpoint: impl Into<String>,
+    ) -> Self {
+        Self {
+            publish_endpoint: publish_endpoint.into(),
+            subscribe_endpoint: subscribe_endpoint.into(),
+            ifname: "ZMQHUB".to_string(),
+            address: None,
+            rx_filter: Vec::new(),
+        }
+    }
+
+    /// Sets the interface name, `ZMQHUB` by default.
+    pub fn ifname(self, ifname: impl Into<String>) -> Self {
+        Self {
+            ifname: ifname.into(),
+            ..self
+        }
+    }
+
+    pub fn address(self, address: u16) -> Self {
+        Self {
+            address: Some(address),
+            ..self
+        }
+    }
+
+    /// Only receives packets for these addresses. Without a filter, every packet
+    /// on the hub is received, so the node can route for others.
+    pub fn rx_filter(self, addresses: impl IntoIterator<Item = u16>) -> Self {
+        Self {
+            rx_filter: addresses.into_iter().collect(),
+            ..self
+        }
+    }
+
+    pub fn publish_endpoint(&self) -> &str {
+        &self.publish_endpoint
+    }
+
+    pub fn subscribe_endpoint(&self) -> &str {
+        &self.subscribe_endpoint
+    }
+
+    /// Checks the endpoints, the name and the rx filter.
+    pub fn validate(&self) -> Result<(), CspError> {
+        check_endpoint("publish", &self.publish_endpoint)?;
+        check_endpoint("subscribe", &self.subscribe_endpoint)?;
+
+        if self.ifname.is_empty() || self.ifname.contains('\0') {
+            return Err(invalid(format!(
+                "Invalid ZMQ interface name `{}`",
+                self.ifname.escape_default()
+            )));
+        }
+
+        let mut addresses = self.address.iter().chain(&self.rx_filter);
+        if let Some(address) = addresses.find(|&&a| a > MAX_ADDRESS) {
+            return Err(invalid(format!(
+                "ZMQ interface `{}`: address {} is larger than the maximum {}",
+                self.ifname, address, MAX_ADDRESS
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> CspError {
+    CspError {
+        kind: CspErrorKind::Inval,
+        message,
+    }
+}
+
+/// Checks that `endpoint` looks like `transport://address`, with a port for TCP.
+fn check_endpoint(which: &str, endpoint: &str) -> Result<(), CspError> {
+    let error = |reason: &str| {
+        invalid(format!(
+            "Invalid ZMQ {} endpoint `{}`: {}",
+            which,
+            endpoint.escape_default(),
+            reason
+        ))
+    };
+
+    let Some((transport, address)) = endpoint.split_once("://") else {
+        return Err(error(
+            "expected `transport://address`, e.g. `tcp://localhost:6000`",
+        ));
+    };
+    if !ZMQ_TRANSPORTS.contains(&transport) {
+        return Err(error(&format!(
+            "unknown transport `{}`, expected one of {}",
+            transport,
+            ZMQ_TRANSPORTS.join(", ")
+        )));
+    }
+    if address.is_empty() {
+        return Err(error("the address is empty"));
+    }
+    if endpoint.contains(char::is_whitespace) || endpoint.contains('\0') {
+        return Err(error("endpoints can't contain spaces"));
+    }
+
+    if transport == "tcp" {
+        let Some((host, port)) = address.rsplit_once(':') else {
+            return Err(error(
+                "TCP endpoints need a port, e.g. `tcp://localhost:6000`",
+            ));
+        };
+        if host.is_empty() {
+            return Err(error("the host is empty"));
+        }
+        if port != "*" && port.parse::<u16>().map_or(true, |port| port == 0) {
+            return Err(error(&format!("`{}` is not a valid port", port)));
+        }
+    }
+
+    Ok(())
+}
+
+impl InterfaceBuilder for CspZmqConfig {
+    fn build(self, address: u16) -> Result<*mut csp_iface_t, CspError> {
+        self.validate()?;
+        let address = self.address.unwrap_or(address);
+
+        // LibCSP copies the name and connects during the call, so nothing is kept
+        let ifname = CString::new(self.ifname).unwrap();
+        let publish_endpoint = CString::new(self.publish_endpoint).unwrap();
+        let subscribe_endpoint = CString::new(self.subscribe_endpoint).unwrap();
+
+        // An empty filter subscribes to everything
+        let rx_filter = self.rx_filter;
+
+        let mut return_interface = std::ptr::null_mut();
+        unsafe {
+            let result = csp_zmqhub_init_w_name_endpoints_rxfilter(
+                ifname.as_ptr(),
+                address,
+                if rx_filter.is_empty() {
+                    std::ptr::null()
+                } else {
+                    rx_filter.as_ptr()
+                },
+                rx_filter.len() as _,
+                publish_endpoint.as_ptr(),
+                subscribe_endpoint.as_ptr(),
+                0,
+                &mut return_interface,
+            );
+            csp_assert!(result, "Failed to initialize ZMQ interface");
+        }
+
+        Ok(return_interface)
+    }
+}
+
+/// The serialized form of a `CspZmqConfig`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ZmqConfigFile {
+    host: Option<String>,
+    publish_endpoint: Option<String>,
+    subscribe_endpoint: Option<String>,
+    ifname: Option<String>,
+    address: Option<u16>,
+    #[serde(default)]
+    rx_filter: Vec<u16>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ZmqConfigFile> for CspZmqConfig {
+    type Error = CspError;
+
+    fn try_from(file: ZmqConfigFile) -> Result<Self, CspError> {
+        let mut config = match (file.host, file.publish_endpoint, file.subscribe_endpoint) {
+            (Some(host), None, None) => Self::host(&host),
+            (None, Some(publish), Some(subscribe)) => Self::endpoints(publish, subscribe),
+            _ => {
+                return Err(invalid(
+                    "ZMQ interfaces need either a `host`, or both a `publish_endpoint` and a `subscribe_endpoint`"
+                        .to_string(),
+                ))
+            }
+        };
+
+        if let Some(ifname) = file.ifname {
+            config = config.ifname(ifname);
+        }
+        if let Some(address) = file.address {
+            config = config.address(address);
+        }
+        config = config.rx_filter(file.rx_filter);
+
+        config.validate()?;
+        Ok(config)
+    }
+}
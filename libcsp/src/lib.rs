@@ -1,9 +1,13 @@
-use std::{sync::Mutex, thread, time::Duration};
+use std::{
+    ffi::CString,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
-use interface::InterfaceBuilder;
+use interface::{CspInterfaceHandle, CspInterfaceInfo, InterfaceBuilder};
 use libcsp_sys::*;
 use once_cell::sync::Lazy;
-use utils::to_owned_c_str_ptr;
+use router::RouterThread;
 
 pub mod interface;
 
@@ -14,51 +18,111 @@ mod route;
 pub use route::*;
 mod connection;
 pub use connection::*;
+mod conn_table;
+pub use conn_table::*;
 
 mod socket;
 pub use socket::*;
+mod datagram;
+pub use datagram::CspDatagramSocket;
 mod port;
 pub use port::*;
 mod client;
 pub use client::*;
+mod options;
+pub use options::*;
+mod rdp;
+pub use rdp::RdpConfig;
+mod debug;
+pub use debug::CspDebugChannel;
+#[cfg(feature = "serde")]
+mod config;
+#[cfg(feature = "serde")]
+pub use config::*;
 
 mod errors;
 use errors::csp_assert;
 pub use errors::{CspError, CspErrorKind};
 
-mod utils;
+mod handles;
+mod router;
+#[cfg(feature = "yaml")]
+mod yaml;
+#[cfg(feature = "zmqproxy")]
+pub mod zmqproxy;
 
 static GLOBAL_LIBCSP_INSTANCE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
 pub struct LibCspBuilder<'a> {
     debug_channels: &'a [CspDebugChannel],
+    router_thread: bool,
     config: LibCspConfig,
 }
 
 impl<'a> LibCspBuilder<'a> {
     pub fn new(config: LibCspConfig) -> Self {
         Self {
-            debug_channels: CspDebugChannel::up_to_error(),
+            debug_channels: CspDebugChannel::up_to_info(),
+            router_thread: true,
             config,
         }
     }
 
+    /// Don't spawn the background router thread.
+    ///
+    /// Packets are then only routed when calling `LibCspInstance::route_work` or
+    /// `LibCspInstance::route_work_for`, which is useful for tests and single-threaded event loops.
+    pub fn manual_routing(mut self) -> Self {
+        self.router_thread = false;
+        self
+    }
+
     /// Sets the debug channels for the global LibCSP instance.
     ///
-    /// LibCSP treats each debug channel individually, for example
-    /// if you set the `Info` channel, you will only see `Info` messages
-    /// without `Error` and `Warn` messages.
+    /// The `Error`, `Packet` and `Protocol` channels turn on the RDP errors, the packet
+    /// trace and the RDP trace inside LibCSP. Every other line LibCSP prints goes to the
+    /// `Info` channel. Lines are dropped unless their channel is set, by default the
+    /// channels up to `Info` are set.
     ///
     /// Use `CspDebugChannel::all()` to set all channels, or `CspDebugChannel::up_to(..)`
     /// to set all channels up to a certain level.
+    ///
+    /// The output is forwarded to the `log` crate, with the level and target
+    /// given by `CspDebugChannel::level` and `CspDebugChannel::target`.
     pub fn debug_channels(mut self, channels: &'a [CspDebugChannel]) -> Self {
         self.debug_channels = channels;
         self
     }
 
+    /// Builds the global LibCSP instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the config is invalid, see `try_build`, or if an instance was already built.
     pub fn build(self) -> LibCspInstance {
+        match self.try_build() {
+            Ok(instance) => instance,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Like `build`, but returns an error if the default connection options or the
+    /// RDP parameters are invalid. The config is checked before LibCSP is initialised,
+    /// so an invalid config doesn't use up the one instance allowed per process.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an instance was already built.
+    pub fn try_build(self) -> Result<LibCspInstance, CspError> {
+        self.config.conn_dfl_so.check().map_err(|err| CspError {
+            kind: err.kind,
+            message: format!("Invalid default connection options: {}", err.message),
+        })?;
+        let rdp = self.config.rdp.check()?;
+
         // This line can only be run once throughout the lifetime of the process.
-        // The global instance lock is aquired within and never released.
+        // The global instance lock is aquired within and never released,
+        // not even on shutdown, as LibCSP can't be initialised a second time.
         let guard_result = GLOBAL_LIBCSP_INSTANCE_LOCK.try_lock();
         let guard = match guard_result {
             Ok(guard) => guard,
@@ -68,67 +132,196 @@ impl<'a> LibCspBuilder<'a> {
         // Leak the guard, so it's never dropped.
         Box::leak(Box::new(guard));
 
+        // Capture the C-side print output before anything gets printed
+        debug::install(self.debug_channels);
+
         unsafe {
             // Initialize buffers
             csp_buffer_init();
         }
 
+        let conf_strings = CspConfStrings::new(&self.config);
+
         unsafe {
             // Set the config for the global instance.
-            let config = self.config.to_csp_conf_t();
+            let config = self.config.to_csp_conf_t(&conf_strings);
             csp_conf = config;
             csp_init();
-            
-            // Add loopback route
-            csp_rtable_set(self.config.address, -1, std::ptr::addr_of_mut!(csp_if_lo), CSP_NO_VIA_ADDRESS as u16);
         }
+        rdp::set_options(&rdp)?;
 
-        unsafe {
-            // Initialize the background router task
-            // TODO: Which parameters are actually needed here?
-            // csp_route_start_task(500, 0);
+        add_loopback_route(self.config.address);
 
-            thread::spawn(|| loop {
-                csp_route_work();
-            });
-        }
+        // Initialize the background router task
+        let router = self.router_thread.then(RouterThread::spawn);
+
+        Ok(LibCspInstance::new(self.config, router, conf_strings))
+    }
 
-        LibCspInstance::new(self.config)
+    /// Builds the instance, and creates the interfaces and routes described in a
+    /// LibCSP YAML configuration file, the same format used by `csp_yaml_init`.
+    ///
+    /// The file is checked before LibCSP is initialised, so a bad entry returns
+    /// an error without using up the one instance allowed per process.
+    #[cfg(feature = "yaml")]
+    pub fn build_with_yaml(
+        self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<LibCspInstance, CspError> {
+        let yaml = yaml::YamlConfig::load(path.as_ref())?;
+        let instance = self.try_build()?;
+        yaml.apply()?;
+        Ok(instance)
     }
 }
 
 /// A global LibCSP instance. There can only be one per process,
 /// due to the structure of the underlying C library.
+///
+/// Dropping the instance shuts it down, see `LibCspInstance::shutdown`.
 pub struct LibCspInstance {
     config: LibCspConfig,
+    router: Option<RouterThread>,
+    _conf_strings: CspConfStrings,
 }
 
 impl LibCspInstance {
     // Private new function, doesn't initialize the config. Other things are initialized by the builder.
-    fn new(config: LibCspConfig) -> Self {
-        Self { config }
+    fn new(
+        config: LibCspConfig,
+        router: Option<RouterThread>,
+        conf_strings: CspConfStrings,
+    ) -> Self {
+        Self {
+            config,
+            router,
+            _conf_strings: conf_strings,
+        }
+    }
+
+    /// Shuts down the global LibCSP instance.
+    ///
+    /// This stops the router thread, closes all the sockets and connections that are
+    /// still open, and frees the configuration strings. Sockets and connections that
+    /// are still held afterwards won't receive anything new.
+    ///
+    /// LibCSP can't be initialised twice, so no other instance can be built afterwards.
+    pub fn shutdown(self) {
+        // The work is done in `Drop`
+    }
+
+    /// Routes a single packet, if one arrives within LibCSP's short internal timeout.
+    /// Returns whether a packet was routed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instance wasn't built with `LibCspBuilder::manual_routing`.
+    pub fn route_work(&self) -> bool {
+        assert!(
+            self.router.is_none(),
+            "route_work can only be used with manual routing"
+        );
+        router::route_once()
+    }
+
+    /// Routes packets until `duration` has passed, and returns the number of packets routed.
+    ///
+    /// The call can overrun `duration` by up to LibCSP's internal routing timeout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instance wasn't built with `LibCspBuilder::manual_routing`.
+    pub fn route_work_for(&self, duration: Duration) -> usize {
+        let deadline = Instant::now() + duration;
+        let mut routed = 0;
+        while Instant::now() < deadline {
+            if self.route_work() {
+                routed += 1;
+            }
+        }
+        routed
     }
 
     /// Associates a route with an interface and adds it to the route table on the global LibCSP instance.
+    ///
+    /// Each call creates a new interface, use `add_interface` and `add_route`
+    /// to have several routes share one interface.
     pub fn add_interface_route(
         &self,
         route: Route,
         interface: impl InterfaceBuilder,
-    ) -> Result<(), CspError> {
-        let int = interface.build(self.config.address)?;
+    ) -> Result<CspInterfaceHandle, CspError> {
+        let handle = self.add_interface(interface)?;
+        self.add_route(route, &handle)?;
+        Ok(handle)
+    }
+
+    /// Creates an interface on the global LibCSP instance, without any routes.
+    pub fn add_interface(
+        &self,
+        interface: impl InterfaceBuilder,
+    ) -> Result<CspInterfaceHandle, CspError> {
+        CspInterfaceHandle::from_ptr(interface.build(self.config.address)?)
+    }
+
+    /// Adds a route through an existing interface to the route table.
+    pub fn add_route(&self, route: Route, interface: &CspInterfaceHandle) -> Result<(), CspError> {
+        let _table = route::lock_table();
         unsafe {
-            let result = csp_rtable_set(route.address, route.netmask, int, route.via);
-            csp_assert!(result, "Failed to add interface");
+            let result = csp_rtable_set(
+                route.address,
+                route.netmask,
+                interface.as_ptr(),
+                route.via,
+            );
+            csp_assert!(result, "Failed to add route");
         }
 
         Ok(())
     }
 
+    /// Makes `interface` the only default interface, and routes every address
+    /// without a more specific route through it.
+    ///
+    /// The default flag of each interface is written without a lock. A packet
+    /// routed at the same time, e.g. by the router thread, can still see the
+    /// previous default. Call it before traffic starts, or with `manual_routing`.
+    pub fn set_default_interface(&self, interface: &CspInterfaceHandle) -> Result<(), CspError> {
+        interface::for_each_iface(|iface| unsafe {
+            (*iface).is_default = (iface == interface.as_ptr()) as u8;
+        });
+        self.add_route(Route::default_address(), interface)
+    }
+
+    /// Finds an interface by name, e.g. one created from a YAML configuration file.
+    pub fn interface(&self, name: &str) -> Option<CspInterfaceHandle> {
+        CspInterfaceHandle::find(name)
+    }
+
     pub fn open_server_socket(&self, port: CspPort) -> Result<CspSocket, CspError> {
+        self.open_server_socket_opts(port, CspSocketOptions::empty())
+    }
+
+    /// Opens a server socket that only accepts connections meeting `opts`,
+    /// e.g. `CspSocketOptions::RDPREQ | CspSocketOptions::HMACREQ` for command ports.
+    pub fn open_server_socket_opts(
+        &self,
+        port: CspPort,
+        opts: CspSocketOptions,
+    ) -> Result<CspSocket, CspError> {
+        let opts = opts.check()?;
+        if opts.contains(CspSocketOptions::CONN_LESS) {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: "Connectionless sockets can't accept connections".to_string(),
+            });
+        }
+
         unsafe {
             // In LibCSP v2.0, we must provide the memory for the socket.
             let socket_ptr = Box::into_raw(Box::new(std::mem::zeroed::<csp_socket_t>()));
-            
+            (*socket_ptr).opts = opts.bits();
+
             csp_bind(socket_ptr, port.as_u8());
             csp_listen(socket_ptr, self.config.connection_backlog);
 
@@ -139,79 +332,174 @@ impl LibCspInstance {
         }
     }
 
+    /// Opens a connectionless socket on `port`, see `CspDatagramSocket`.
+    pub fn open_datagram_socket(&self, port: CspPort) -> Result<CspDatagramSocket, CspError> {
+        self.open_datagram_socket_opts(port, CspSocketOptions::empty())
+    }
+
+    /// Like `open_datagram_socket`, only receiving packets that meet `opts`,
+    /// e.g. `CspSocketOptions::CRC32REQ`. `CspSocketOptions::CONN_LESS` is added to `opts`.
+    pub fn open_datagram_socket_opts(
+        &self,
+        port: CspPort,
+        opts: CspSocketOptions,
+    ) -> Result<CspDatagramSocket, CspError> {
+        let opts = (opts | CspSocketOptions::CONN_LESS).check()?;
+        if opts.contains(CspSocketOptions::RDPREQ) {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: "Connectionless sockets can't require RDP".to_string(),
+            });
+        }
+
+        unsafe {
+            let socket_ptr = Box::into_raw(Box::new(std::mem::zeroed::<csp_socket_t>()));
+            (*socket_ptr).opts = opts.bits();
+
+            let result = csp_bind(socket_ptr, port.as_u8());
+            if result != CSP_ERR_NONE as i32 {
+                let _ = Box::from_raw(socket_ptr);
+                csp_assert!(result, &format!("Failed to bind port {}", port.as_u8()));
+            }
+            csp_listen(socket_ptr, self.config.connection_backlog);
+
+            Ok(CspDatagramSocket::from_ptr(socket_ptr, port))
+        }
+    }
+
     pub fn server_sync_socket_builder(&self) -> Result<CspSocketBuilder<'_, ()>, CspError> {
-        let socket = self.open_server_socket(CspPort::any_port())?;
+        self.server_sync_socket_builder_opts(CspSocketOptions::empty())
+    }
+
+    /// Like `server_sync_socket_builder`, with the socket options applied to every bound port.
+    pub fn server_sync_socket_builder_opts(
+        &self,
+        opts: CspSocketOptions,
+    ) -> Result<CspSocketBuilder<'_, ()>, CspError> {
+        let socket = self.open_server_socket_opts(CspPort::any_port(), opts)?;
         Ok(CspSocketBuilder::new(socket))
     }
 
+    /// Sets the RDP parameters of every connection opened from now on,
+    /// see `CspClient::connect_rdp` to set them for a single connection.
+    pub fn set_rdp_config(&self, rdp: &RdpConfig) -> Result<(), CspError> {
+        rdp::set_options(rdp)
+    }
+
+    /// The RDP parameters that new connections get.
+    pub fn rdp_config(&self) -> RdpConfig {
+        rdp::options()
+    }
+
     pub fn client(&self) -> CspClient {
         CspClient::new(&self.config)
     }
 
+    /// Lists the connections in the LibCSP connection pool, for spotting connections
+    /// that were never accepted or were leaked, and ones that were closed underneath
+    /// their holder.
+    pub fn connections(&self) -> Vec<CspConnInfo> {
+        handles::connections()
+    }
+
     pub fn print_conn_table(&self) {
-        unsafe {
+        debug::with_stdout(|| unsafe {
             csp_conn_print_table();
+        });
+    }
+
+    /// Lists the interfaces with their live packet counters.
+    pub fn interfaces(&self) -> Vec<CspInterfaceInfo> {
+        interface::interfaces()
+    }
+
+    /// Resets the packet counters of the interface called `name`.
+    ///
+    /// LibCSP updates the counters without a lock, so a packet sent or received
+    /// while they are reset, e.g. by the router thread, can be counted on top of
+    /// the old value. Reset them while the link is idle, or with `manual_routing`.
+    pub fn reset_interface_counters(&self, name: &str) -> Result<(), CspError> {
+        if interface::reset_counters(|info| info.name == name) == 0 {
+            return Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: format!("No interface called `{}`", name),
+            });
         }
+        Ok(())
+    }
+
+    /// Resets the packet counters of every interface, see `reset_interface_counters`.
+    pub fn reset_all_interface_counters(&self) {
+        interface::reset_counters(|_| true);
     }
 
     pub fn print_iflist(&self) {
-        unsafe {
+        debug::with_stdout(|| unsafe {
             csp_iflist_print();
-        }
+        });
     }
 
-    pub fn print_rtable(&self) {
-        unsafe {
-            csp_rtable_print();
-        }
+    /// Lists the route table, with the name of the interface each route goes through.
+    pub fn routes(&self) -> Vec<(Route, String)> {
+        route::routes()
     }
-}
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum CspDebugChannel {
-    /// Error
-    Error = 0,
-    /// Warning
-    Warn = 1,
-    /// Informational
-    Info = 2,
-    /// Buffer, e.g. csp_packet get/free
-    Buffer = 3,
-    /// Packet routing
-    Packet = 4,
-    /// Protocol, i.e. RDP
-    Protocol = 5,
-    /// Locking, i.e. semaphore
-    Lock = 6,
-}
+    /// Finds the route, and its interface name, that packets to `address` would take.
+    pub fn find_route(&self, address: u16) -> Option<(Route, String)> {
+        route::find_route(address)
+    }
 
-impl CspDebugChannel {
-    pub fn all() -> &'static [Self; 7] {
-        &[
-            Self::Error,
-            Self::Warn,
-            Self::Info,
-            Self::Buffer,
-            Self::Packet,
-            Self::Protocol,
-            Self::Lock,
-        ]
+    /// Removes the route with the same address and netmask as `route`.
+    ///
+    /// LibCSP can't remove a single route, so the table is cleared and the other
+    /// routes are set again. A packet routed in that moment, e.g. by the router
+    /// thread, finds no route and is dropped.
+    pub fn remove_route(&self, route: Route) -> Result<(), CspError> {
+        let _table = route::lock_table();
+        route::remove_route(route)
     }
 
-    pub fn up_to(self) -> &'static [Self] {
-        &Self::all()[..=self as usize]
+    /// Removes every route, apart from the loopback route to this node.
+    pub fn clear_routes(&self) {
+        let _table = route::lock_table();
+        route::clear_routes();
+        add_loopback_route(self.config.address);
     }
 
-    pub fn up_to_error() -> &'static [Self] {
-        Self::up_to(Self::Error)
+    /// Adds the routes in the LibCSP textual format, e.g. `"0/0 ZMQ, 10/5 CAN 12"`,
+    /// where each route is `address/netmask interface [via]`.
+    /// The interfaces must already exist. Returns the number of routes added.
+    pub fn load_routes(&self, routes: &str) -> Result<usize, CspError> {
+        let _table = route::lock_table();
+        route::load_routes(routes)
     }
 
-    pub fn up_to_warn() -> &'static [Self] {
-        Self::up_to(Self::Warn)
+    /// Saves the route table in the textual format read by `load_routes`.
+    pub fn save_routes(&self) -> Result<String, CspError> {
+        route::save_routes()
     }
 
-    pub fn up_to_info() -> &'static [Self] {
-        Self::up_to(Self::Info)
+    pub fn print_rtable(&self) {
+        debug::with_stdout(|| unsafe {
+            csp_rtable_print();
+        });
+    }
+}
+
+impl Drop for LibCspInstance {
+    fn drop(&mut self) {
+        if let Some(router) = self.router.take() {
+            router.stop();
+        }
+
+        handles::close_all();
+
+        unsafe {
+            // Don't leave LibCSP pointing at the strings that are about to be freed.
+            csp_conf.hostname = c"".as_ptr();
+            csp_conf.model = c"".as_ptr();
+            csp_conf.revision = c"".as_ptr();
+        }
     }
 }
 
@@ -221,8 +509,10 @@ pub struct LibCspConfig {
     pub model: String,
     pub revision: String,
     pub dedup: u8,
-    pub conn_dfl_so: u32,
+    pub conn_dfl_so: CspConnOptions,
     pub connection_backlog: usize,
+    /// RDP parameters of the connections, the LibCSP defaults if not set
+    pub rdp: RdpConfig,
 
     /// Packet timeout on service messages that are handled internally
     pub service_timeout: Duration,
@@ -257,21 +547,25 @@ impl LibCspConfig {
     }
 
     /// Refer to the LibCSP documentation
-    pub fn conn_dfl_so(self, conn_dfl_so: u32) -> Self {
+    pub fn conn_dfl_so(self, conn_dfl_so: CspConnOptions) -> Self {
         Self {
             conn_dfl_so,
             ..self
         }
     }
 
-    fn to_csp_conf_t(&self) -> csp_conf_t {
+    pub fn rdp(self, rdp: RdpConfig) -> Self {
+        Self { rdp, ..self }
+    }
+
+    fn to_csp_conf_t(&self, strings: &CspConfStrings) -> csp_conf_t {
         csp_conf_t {
             version: 2,
             address: self.address,
-            hostname: to_owned_c_str_ptr(&self.hostname),
-            model: to_owned_c_str_ptr(&self.model),
-            revision: to_owned_c_str_ptr(&self.revision),
-            conn_dfl_so: self.conn_dfl_so,
+            hostname: strings.hostname.as_ptr(),
+            model: strings.model.as_ptr(),
+            revision: strings.revision.as_ptr(),
+            conn_dfl_so: self.conn_dfl_so.bits(),
             dedup: self.dedup,
         }
     }
@@ -285,9 +579,38 @@ impl Default for LibCspConfig {
             model: "{model unspecified}".to_string(),
             revision: "{resvision unspecified}".to_string(),
             dedup: 1,
-            conn_dfl_so: CSP_O_NONE,
+            conn_dfl_so: CspConnOptions::empty(),
             connection_backlog: 64,
+            rdp: RdpConfig::default(),
             service_timeout: Duration::from_millis(100),
         }
     }
 }
+
+fn add_loopback_route(address: u16) {
+    unsafe {
+        csp_rtable_set(
+            address,
+            -1,
+            std::ptr::addr_of_mut!(csp_if_lo),
+            CSP_NO_VIA_ADDRESS as u16,
+        );
+    }
+}
+
+/// The strings that `csp_conf` points to, owned by the instance for as long as LibCSP uses them.
+struct CspConfStrings {
+    hostname: CString,
+    model: CString,
+    revision: CString,
+}
+
+impl CspConfStrings {
+    fn new(config: &LibCspConfig) -> Self {
+        Self {
+            hostname: CString::new(config.hostname.as_str()).unwrap(),
+            model: CString::new(config.model.as_str()).unwrap(),
+            revision: CString::new(config.revision.as_str()).unwrap(),
+        }
+    }
+}
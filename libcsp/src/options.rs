@@ -0,0 +1,122 @@
+use bitflags::bitflags;
+use libcsp_sys::{
+    CSP_O_CRC32, CSP_O_HMAC, CSP_O_NOCRC32, CSP_O_NOHMAC, CSP_O_NORDP, CSP_O_RDP, CSP_O_SAME,
+    CSP_SO_CONN_LESS, CSP_SO_CRC32PROHIB, CSP_SO_CRC32REQ, CSP_SO_HMACPROHIB, CSP_SO_HMACREQ,
+    CSP_SO_RDPPROHIB, CSP_SO_RDPREQ,
+};
+
+use crate::{CspError, CspErrorKind};
+
+bitflags! {
+    /// Options for a connection, mirroring the `CSP_O_*` flags of LibCSP.
+    ///
+    /// The `NO*` flags override the socket default, and can't be combined with their counterpart.
+    /// Use `CspConnOptions::check` to catch such combinations.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(transparent))]
+    pub struct CspConnOptions: u32 {
+        /// Enable RDP
+        const RDP = CSP_O_RDP;
+        /// Disable RDP
+        const NORDP = CSP_O_NORDP;
+        /// Enable HMAC
+        const HMAC = CSP_O_HMAC;
+        /// Disable HMAC
+        const NOHMAC = CSP_O_NOHMAC;
+        /// Enable CRC32
+        const CRC32 = CSP_O_CRC32;
+        /// Disable CRC32
+        const NOCRC32 = CSP_O_NOCRC32;
+        /// Copy the options from the incoming packet, only used by replies
+        const SAME = CSP_O_SAME;
+    }
+}
+
+impl CspConnOptions {
+    /// Returns the options if no flag is combined with its opposite, e.g. `RDP` with `NORDP`.
+    pub fn check(self) -> Result<Self, CspError> {
+        let pairs = [
+            (Self::RDP, Self::NORDP, "RDP"),
+            (Self::HMAC, Self::NOHMAC, "HMAC"),
+            (Self::CRC32, Self::NOCRC32, "CRC32"),
+        ];
+
+        for (enable, disable, name) in pairs {
+            if self.contains(enable | disable) {
+                return Err(CspError {
+                    kind: CspErrorKind::Inval,
+                    message: format!(
+                        "Conflicting connection options, both {} and NO{} are set",
+                        name, name
+                    ),
+                });
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+impl Default for CspConnOptions {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+bitflags! {
+    /// Options for a socket, mirroring the `CSP_SO_*` flags of LibCSP.
+    ///
+    /// Incoming connections that don't meet the requirements are dropped by LibCSP.
+    /// A requirement can't be combined with the matching prohibition,
+    /// use `CspSocketOptions::check` to catch such combinations.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(transparent))]
+    pub struct CspSocketOptions: u32 {
+        /// Require RDP
+        const RDPREQ = CSP_SO_RDPREQ;
+        /// Prohibit RDP
+        const RDPPROHIB = CSP_SO_RDPPROHIB;
+        /// Require HMAC
+        const HMACREQ = CSP_SO_HMACREQ;
+        /// Prohibit HMAC
+        const HMACPROHIB = CSP_SO_HMACPROHIB;
+        /// Require CRC32
+        const CRC32REQ = CSP_SO_CRC32REQ;
+        /// Prohibit CRC32
+        const CRC32PROHIB = CSP_SO_CRC32PROHIB;
+        /// Connectionless socket, packets are received without accepting a connection
+        const CONN_LESS = CSP_SO_CONN_LESS;
+    }
+}
+
+impl CspSocketOptions {
+    /// Returns the options if no requirement is combined with its prohibition,
+    /// e.g. `RDPREQ` with `RDPPROHIB`.
+    pub fn check(self) -> Result<Self, CspError> {
+        let pairs = [
+            (Self::RDPREQ, Self::RDPPROHIB, "RDP"),
+            (Self::HMACREQ, Self::HMACPROHIB, "HMAC"),
+            (Self::CRC32REQ, Self::CRC32PROHIB, "CRC32"),
+        ];
+
+        for (require, prohibit, name) in pairs {
+            if self.contains(require | prohibit) {
+                return Err(CspError {
+                    kind: CspErrorKind::Inval,
+                    message: format!(
+                        "Conflicting socket options, {} is both required and prohibited",
+                        name
+                    ),
+                });
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+impl Default for CspSocketOptions {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
@@ -0,0 +1,232 @@
+use std::{sync::Mutex, time::Duration};
+
+use libcsp_sys::{csp_rdp_get_opt, csp_rdp_set_opt, CSP_RDP_MAX_WINDOW};
+
+use crate::{CspError, CspErrorKind};
+
+/// LibCSP keeps a single set of RDP options, which `with_options` changes for a moment.
+static RDP_OPTIONS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Parameters of the Reliable Datagram Protocol, mirroring `csp_rdp_set_opt`.
+///
+/// LibCSP copies them into a connection when it is opened, and the remote end adopts
+/// them from the connection request. They can be set for the whole instance, with
+/// `LibCspConfig::rdp` or `LibCspInstance::set_rdp_config`, or for a single connection
+/// with `CspClient::connect_rdp`.
+///
+/// ```
+/// use std::time::Duration;
+/// use libcsp::RdpConfig;
+///
+/// // A long, slow radio link
+/// let rdp = RdpConfig::default()
+///     .window_size(5)
+///     .conn_timeout(Duration::from_secs(30))
+///     .packet_timeout(Duration::from_secs(3));
+/// assert!(rdp.check().is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RdpConfig {
+    /// Packets in flight before waiting for an acknowledgement
+    pub window_size: u32,
+    /// Time without any packet from the remote end before the connection is closed
+    pub conn_timeout: Duration,
+    /// Time before an unacknowledged packet is sent again
+    pub packet_timeout: Duration,
+    /// Acknowledge several packets at once, instead of each one
+    pub delayed_acks: bool,
+    /// With delayed ACKs, the longest time an acknowledgement is held back
+    pub ack_timeout: Duration,
+    /// With delayed ACKs, the number of packets acknowledged at once
+    pub ack_delay_count: u32,
+}
+
+impl Default for RdpConfig {
+    /// The LibCSP defaults.
+    fn default() -> Self {
+        Self {
+            window_size: 4,
+            conn_timeout: Duration::from_millis(10_000),
+            packet_timeout: Duration::from_millis(1_000),
+            delayed_acks: true,
+            ack_timeout: Duration::from_millis(250),
+            ack_delay_count: 2,
+        }
+    }
+}
+
+impl RdpConfig {
+    pub fn window_size(self, window_size: u32) -> Self {
+        Self {
+            window_size,
+            ..self
+        }
+    }
+
+    pub fn conn_timeout(self, conn_timeout: Duration) -> Self {
+        Self {
+            conn_timeout,
+            ..self
+        }
+    }
+
+    pub fn packet_timeout(self, packet_timeout: Duration) -> Self {
+        Self {
+            packet_timeout,
+            ..self
+        }
+    }
+
+    pub fn delayed_acks(self, delayed_acks: bool) -> Self {
+        Self {
+            delayed_acks,
+            ..self
+        }
+    }
+
+    pub fn ack_timeout(self, ack_timeout: Duration) -> Self {
+        Self {
+            ack_timeout,
+            ..self
+        }
+    }
+
+    pub fn ack_delay_count(self, ack_delay_count: u32) -> Self {
+        Self {
+            ack_delay_count,
+            ..self
+        }
+    }
+
+    /// Returns the parameters if LibCSP can use them: a window of 1 up to
+    /// `CSP_RDP_MAX_WINDOW` packets, timeouts of at least a millisecond, and a
+    /// packet timeout below the connection timeout. With delayed ACKs, the ACK
+    /// timeout must be below the packet timeout, and the ACK delay count within the window.
+    pub fn check(self) -> Result<Self, CspError> {
+        let error = |message: String| {
+            Err(CspError {
+                kind: CspErrorKind::Inval,
+                message: format!("Invalid RDP options: {}", message),
+            })
+        };
+
+        if !(1..=CSP_RDP_MAX_WINDOW).contains(&self.window_size) {
+            return error(format!(
+                "the window size {} is not between 1 and {}",
+                self.window_size, CSP_RDP_MAX_WINDOW
+            ));
+        }
+
+        let timeouts = [
+            ("connection", self.conn_timeout),
+            ("packet", self.packet_timeout),
+            ("ACK", self.ack_timeout),
+        ];
+        for (name, timeout) in timeouts {
+            if timeout < Duration::from_millis(1) || millis(timeout).is_none() {
+                return error(format!(
+                    "the {} timeout {:?} is not between 1 ms and {} ms",
+                    name,
+                    timeout,
+                    u32::MAX
+                ));
+            }
+        }
+        if self.packet_timeout >= self.conn_timeout {
+            return error(format!(
+                "the packet timeout {:?} is not below the connection timeout {:?}",
+                self.packet_timeout, self.conn_timeout
+            ));
+        }
+
+        if self.delayed_acks {
+            if self.ack_timeout >= self.packet_timeout {
+                return error(format!(
+                    "the ACK timeout {:?} is not below the packet timeout {:?}",
+                    self.ack_timeout, self.packet_timeout
+                ));
+            }
+            if !(1..=self.window_size).contains(&self.ack_delay_count) {
+                return error(format!(
+                    "the ACK delay count {} is not between 1 and the window size {}",
+                    self.ack_delay_count, self.window_size
+                ));
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+fn millis(duration: Duration) -> Option<u32> {
+    u32::try_from(duration.as_millis()).ok()
+}
+
+/// Sets the options of the instance, for every connection opened from now on.
+pub(crate) fn set_options(config: &RdpConfig) -> Result<(), CspError> {
+    let config = config.check()?;
+    let _lock = RDP_OPTIONS_LOCK.lock().unwrap();
+    unsafe { apply(&config) };
+    Ok(())
+}
+
+pub(crate) fn options() -> RdpConfig {
+    let _lock = RDP_OPTIONS_LOCK.lock().unwrap();
+    unsafe { read() }
+}
+
+/// Runs `f` with `config` in place of the options of the instance, which are restored afterwards.
+pub(crate) fn with_options<T>(config: &RdpConfig, f: impl FnOnce() -> T) -> Result<T, CspError> {
+    let config = config.check()?;
+    let _lock = RDP_OPTIONS_LOCK.lock().unwrap();
+    unsafe {
+        let previous = read();
+        apply(&config);
+        let result = f();
+        apply(&previous);
+        Ok(result)
+    }
+}
+
+/// # Safety
+///
+/// The options must have been checked, and `RDP_OPTIONS_LOCK` held.
+unsafe fn apply(config: &RdpConfig) {
+    csp_rdp_set_opt(
+        config.window_size,
+        config.conn_timeout.as_millis() as u32,
+        config.packet_timeout.as_millis() as u32,
+        config.delayed_acks as u32,
+        config.ack_timeout.as_millis() as u32,
+        config.ack_delay_count,
+    );
+}
+
+/// # Safety
+///
+/// `RDP_OPTIONS_LOCK` must be held.
+unsafe fn read() -> RdpConfig {
+    let mut window_size = 0;
+    let mut conn_timeout = 0;
+    let mut packet_timeout = 0;
+    let mut delayed_acks = 0;
+    let mut ack_timeout = 0;
+    let mut ack_delay_count = 0;
+    csp_rdp_get_opt(
+        &mut window_size,
+        &mut conn_timeout,
+        &mut packet_timeout,
+        &mut delayed_acks,
+        &mut ack_timeout,
+        &mut ack_delay_count,
+    );
+
+    RdpConfig {
+        window_size,
+        conn_timeout: Duration::from_millis(conn_timeout.into()),
+        packet_timeout: Duration::from_millis(packet_timeout.into()),
+        delayed_acks: delayed_acks != 0,
+        ack_timeout: Duration::from_millis(ack_timeout.into()),
+        ack_delay_count,
+    }
+}
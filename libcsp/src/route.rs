@@ -1,4 +1,16 @@
-use libcsp_sys::CSP_NO_VIA_ADDRESS;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_void},
+    sync::{Mutex, MutexGuard},
+};
+
+use libcsp_sys::{
+    csp_id_get_host_bits, csp_iface_t, csp_route_t, csp_rtable_check, csp_rtable_clear,
+    csp_rtable_find_route, csp_rtable_iterate, csp_rtable_load, csp_rtable_save, csp_rtable_set,
+    CSP_NO_VIA_ADDRESS,
+};
+
+use crate::{csp_assert, CspError, CspErrorKind};
 
 /// Represents a route for the CSP protocol network.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -78,3 +90,145 @@ impl Route {
         Self::new(0).netmask(0)
     }
 }
+
+/// Room for one route in the textual format, e.g. `"10/5 ZMQ 12, "`. The buffer
+/// grows if long interface names don't fit.
+const SAVED_ROUTE_LEN: usize = 64;
+
+/// Serialises the changes to the route table, which LibCSP doesn't lock.
+static RTABLE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Held by everything that changes the route table, so concurrent changes don't
+/// undo each other, e.g. a route added while `remove_route` rebuilds the table.
+pub(crate) fn lock_table() -> MutexGuard<'static, ()> {
+    RTABLE_LOCK.lock().unwrap()
+}
+
+impl Route {
+    fn from_csp_route(route: &csp_route_t) -> Self {
+        Self {
+            address: route.address,
+            netmask: route.netmask as i32,
+            via: route.via,
+        }
+    }
+}
+
+fn iface_name(iface: *const csp_iface_t) -> String {
+    unsafe {
+        if iface.is_null() || (*iface).name.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr((*iface).name).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// Collects every entry of the route table, with the interface it goes through.
+fn route_entries() -> Vec<(Route, *mut csp_iface_t)> {
+    unsafe extern "C" fn collect(ctx: *mut c_void, route: *mut csp_route_t) -> bool {
+        let entries = &mut *(ctx as *mut Vec<(Route, *mut csp_iface_t)>);
+        entries.push((Route::from_csp_route(&*route), (*route).iface));
+        true
+    }
+
+    let mut entries: Vec<(Route, *mut csp_iface_t)> = Vec::new();
+    unsafe { csp_rtable_iterate(Some(collect), &mut entries as *mut _ as *mut c_void) };
+    entries
+}
+
+pub(crate) fn routes() -> Vec<(Route, String)> {
+    route_entries()
+        .into_iter()
+        .map(|(route, iface)| (route, iface_name(iface)))
+        .collect()
+}
+
+pub(crate) fn find_route(address: u16) -> Option<(Route, String)> {
+    let route = unsafe { csp_rtable_find_route(address) };
+    if route.is_null() {
+        return None;
+    }
+
+    let route = unsafe { &*route };
+    Some((Route::from_csp_route(route), iface_name(route.iface)))
+}
+
+/// LibCSP can't remove a single route, so the table is rebuilt without it.
+/// The table lock must be held.
+pub(crate) fn remove_route(route: Route) -> Result<(), CspError> {
+    // A netmask of -1 is stored as all the host bits
+    let netmask = if route.netmask < 0 {
+        unsafe { csp_id_get_host_bits() }
+    } else {
+        route.netmask
+    };
+
+    let entries = route_entries();
+    let is_match = |entry: &Route| entry.address == route.address && entry.netmask == netmask;
+
+    if !entries.iter().any(|(entry, _)| is_match(entry)) {
+        return Err(CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("No route to {}/{}", route.address, route.netmask),
+        });
+    }
+
+    unsafe { csp_rtable_clear() };
+    for (entry, iface) in entries.into_iter().filter(|(entry, _)| !is_match(entry)) {
+        unsafe {
+            let result = csp_rtable_set(entry.address, entry.netmask, iface, entry.via);
+            csp_assert!(result, "Failed to restore route");
+        }
+    }
+
+    Ok(())
+}
+
+/// The table lock must be held.
+pub(crate) fn clear_routes() {
+    unsafe { csp_rtable_clear() };
+}
+
+/// Loads routes in the LibCSP textual format, e.g. `"0/0 ZMQ, 10/5 CAN 12"`,
+/// and returns the number of routes loaded. The table lock must be held.
+pub(crate) fn load_routes(text: &str) -> Result<usize, CspError> {
+    let text = CString::new(text).map_err(|e| CspError {
+        kind: CspErrorKind::Inval,
+        message: format!("Invalid route table: {}", e),
+    })?;
+
+    unsafe {
+        // Check the whole table first, so a bad entry doesn't leave it half loaded
+        let result = csp_rtable_check(text.as_ptr());
+        if result < 0 {
+            csp_assert!(result, "Invalid route table");
+        }
+
+        let result = csp_rtable_load(text.as_ptr());
+        if result < 0 {
+            csp_assert!(result, "Failed to load route table");
+        }
+        Ok(result as usize)
+    }
+}
+
+/// Saves the route table in the LibCSP textual format.
+pub(crate) fn save_routes() -> Result<String, CspError> {
+    let mut size = (route_entries().len() + 1) * SAVED_ROUTE_LEN;
+
+    loop {
+        let mut buffer = vec![0 as c_char; size];
+        unsafe {
+            let result = csp_rtable_save(buffer.as_mut_ptr(), buffer.len());
+            csp_assert!(result, "Failed to save route table");
+        }
+
+        // LibCSP cuts the table short when it doesn't fit, which leaves the buffer full
+        let saved = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+        if saved.to_bytes().len() + 1 < size {
+            return Ok(saved.to_string_lossy().into_owned());
+        }
+        size *= 2;
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    os::raw::c_int,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use libcsp_sys::{csp_route_work, CSP_ERR_NONE};
+
+/// Routes a single packet, waiting a short while for one to arrive.
+/// Returns whether a packet was routed.
+pub(crate) fn route_once() -> bool {
+    unsafe { csp_route_work() == CSP_ERR_NONE as c_int }
+}
+
+/// The background thread that runs the LibCSP router.
+pub(crate) struct RouterThread {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl RouterThread {
+    pub(crate) fn spawn() -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let thread = thread::Builder::new()
+            .name("csp-router".to_string())
+            .spawn(move || {
+                // `csp_route_work` returns after a short timeout when there is nothing to route,
+                // so the stop flag is checked regularly.
+                while !thread_stop.load(Ordering::Relaxed) {
+                    route_once();
+                }
+            })
+            .expect("Failed to spawn the CSP router thread");
+
+        Self { stop, thread }
+    }
+
+    /// Signals the router thread to stop, and waits for it to finish.
+    pub(crate) fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.join().ok();
+    }
+}
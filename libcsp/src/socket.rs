@@ -1,10 +1,10 @@
 use std::{ptr::NonNull, time::Duration};
 
 use libcsp_sys::{
-    csp_accept, csp_socket_close, csp_socket_t,
+    csp_accept, csp_socket_t,
 };
 
-use crate::CspConnection;
+use crate::{handles, CspConnKind, CspConnection};
 
 /// Represents a CSP socket.
 ///
@@ -25,6 +25,7 @@ impl CspSocket {
     ///
     /// A `CspSocket` instance.
     pub(crate) fn from_ptr(socket: *mut csp_socket_t, service_timeout_ms: u32) -> Self {
+        handles::register_socket(socket);
         Self {
             service_timeout_ms,
             socket: NonNull::new(socket).expect("Socket pointer cannot be null"),
@@ -47,7 +48,7 @@ impl CspSocket {
         if conn.is_null() {
             None
         } else {
-            Some(CspConnection::new(conn, self.service_timeout_ms))
+            Some(CspConnection::new(conn, CspConnKind::Server, self.service_timeout_ms))
         }
     }
 
@@ -66,7 +67,7 @@ impl CspSocket {
             if conn.is_null() {
                 continue;
             } else {
-                return CspConnection::new(conn, self.service_timeout_ms);
+                return CspConnection::new(conn, CspConnKind::Server, self.service_timeout_ms);
             }
         }
     }
@@ -74,8 +75,8 @@ impl CspSocket {
 
 impl Drop for CspSocket {
     fn drop(&mut self) {
+        handles::close_socket(self.socket.as_ptr());
         unsafe {
-            csp_socket_close(self.socket.as_ptr());
             // The memory was allocated with Box::into_raw in lib.rs
             let _ = Box::from_raw(self.socket.as_ptr());
         }
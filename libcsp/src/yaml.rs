@@ -0,0 +1,233 @@
+use std::{
+    ffi::CString,
+    os::raw::{c_char, c_uint},
+    path::Path,
+};
+
+use libcsp_sys::{csp_iflist_get_by_name, csp_rtable_set, csp_yaml_init};
+use serde_yaml::{Mapping, Value};
+
+use crate::{csp_assert, route, CspError, CspErrorKind, Route};
+
+/// Drivers understood by the LibCSP YAML parser.
+const KNOWN_DRIVERS: &[&str] = &["zmq", "can", "kiss", "udp", "tun"];
+
+/// The largest address in the CSP v2 header.
+const MAX_ADDRESS: u16 = 0x3fff;
+
+/// A LibCSP YAML configuration file that has been checked for bad entries.
+pub(crate) struct YamlConfig {
+    path: CString,
+    interfaces: Vec<YamlInterface>,
+}
+
+struct YamlInterface {
+    name: String,
+    address: u16,
+    netmask: Option<u8>,
+    default: bool,
+}
+
+impl YamlConfig {
+    /// Reads and validates the file, without touching LibCSP yet.
+    pub(crate) fn load(path: &Path) -> Result<Self, CspError> {
+        let error = |message: String| CspError {
+            kind: CspErrorKind::Inval,
+            message: format!("{}: {}", path.display(), message),
+        };
+
+        let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let entries: Vec<Mapping> = serde_yaml::from_str(&text)
+            .map_err(|e| error(format!("expected a list of interfaces, {}", e)))?;
+
+        let mut interfaces: Vec<YamlInterface> = Vec::with_capacity(entries.len());
+        for (index, entry) in entries.iter().enumerate() {
+            let interface = YamlInterface::parse(entry)
+                .map_err(|message| error(format!("interface #{}: {}", index + 1, message)))?;
+
+            if interfaces.iter().any(|other| other.name == interface.name) {
+                return Err(error(format!(
+                    "interface #{}: duplicate name `{}`",
+                    index + 1,
+                    interface.name
+                )));
+            }
+            interfaces.push(interface);
+        }
+
+        let path = path
+            .to_str()
+            .ok_or_else(|| error("path is not valid UTF-8".to_string()))?;
+        Ok(Self {
+            path: CString::new(path).map_err(|e| error(e.to_string()))?,
+            interfaces,
+        })
+    }
+
+    /// Creates the interfaces with LibCSP, and adds a route for each of them.
+    ///
+    /// `csp_yaml_init` doesn't return anything in LibCSP v2.0, so an interface that
+    /// failed to initialise is only noticed by its absence from the interface list.
+    pub(crate) fn apply(&self) -> Result<(), CspError> {
+        let mut default_address: c_uint = 0;
+        unsafe { csp_yaml_init(self.path.as_ptr() as *mut c_char, &mut default_address) };
+
+        // Every interface must exist before any route is added
+        let mut ifaces = Vec::with_capacity(self.interfaces.len());
+        for interface in &self.interfaces {
+            let name = CString::new(interface.name.as_str()).unwrap();
+            let iface = unsafe { csp_iflist_get_by_name(name.as_ptr()) };
+            if iface.is_null() {
+                return Err(CspError {
+                    kind: CspErrorKind::Driver,
+                    message: format!(
+                        "{}: interface `{}` failed to initialise",
+                        self.path.to_string_lossy(),
+                        interface.name
+                    ),
+                });
+            }
+            ifaces.push((interface, iface));
+        }
+
+        // LibCSP reports the address of the interface it took as the default,
+        // but leaves the default route to the caller
+        let default_iface = ifaces
+            .iter()
+            .find(|(interface, _)| {
+                interface.default && c_uint::from(interface.address) == default_address
+            })
+            .copied();
+        if let Some(interface) = self.interfaces.iter().find(|interface| interface.default) {
+            if default_iface.is_none() {
+                return Err(CspError {
+                    kind: CspErrorKind::Driver,
+                    message: format!(
+                        "{}: LibCSP didn't take `{}` as the default interface",
+                        self.path.to_string_lossy(),
+                        interface.name
+                    ),
+                });
+            }
+        }
+
+        let _table = route::lock_table();
+        for (interface, iface) in ifaces {
+            let route = match interface.netmask {
+                Some(bits) => Route::new(interface.address).netmask_bits(bits),
+                None => Route::new(interface.address),
+            };
+            unsafe {
+                let result = csp_rtable_set(route.address, route.netmask, iface, route.via);
+                csp_assert!(
+                    result,
+                    &format!("Failed to add route for `{}`", interface.name)
+                );
+            }
+        }
+
+        if let Some((interface, iface)) = default_iface {
+            let route = Route::default_address();
+            unsafe {
+                let result = csp_rtable_set(route.address, route.netmask, iface, route.via);
+                csp_assert!(
+                    result,
+                    &format!("Failed to add default route for `{}`", interface.name)
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl YamlInterface {
+    /// Checks the keys that are needed to create the interface and its routes. Other
+    /// keys are left to LibCSP, which prints a message for any key it doesn't know.
+    fn parse(entry: &Mapping) -> Result<Self, String> {
+        let name = required(entry, "name")?;
+        if name.is_empty() {
+            return Err("`name` is empty".to_string());
+        }
+
+        let driver = required(entry, "driver")?;
+        if !KNOWN_DRIVERS.contains(&driver.as_str()) {
+            return Err(format!(
+                "unknown driver `{}`, expected one of {}",
+                driver,
+                KNOWN_DRIVERS.join(", ")
+            ));
+        }
+        if matches!(driver.as_str(), "can" | "kiss") {
+            required(entry, "device")?;
+        }
+        if driver == "kiss" {
+            required(entry, "baudrate")?;
+        }
+
+        let address: u16 = number(entry, "address")?.ok_or("missing `address`")?;
+        if address > MAX_ADDRESS {
+            return Err(format!(
+                "`address` {} is larger than the maximum {}",
+                address, MAX_ADDRESS
+            ));
+        }
+
+        let netmask: Option<u8> = number(entry, "netmask")?;
+        if let Some(netmask) = netmask {
+            if netmask > 14 {
+                return Err(format!("`netmask` {} is larger than 14 bits", netmask));
+            }
+        }
+
+        number::<u32>(entry, "baudrate")?;
+        number::<u16>(entry, "listen_port")?;
+        number::<u16>(entry, "remote_port")?;
+
+        let default = match optional(entry, "default")?.as_deref() {
+            None | Some("false") => false,
+            Some("true") => true,
+            Some(other) => return Err(format!("`default` must be true or false, not `{}`", other)),
+        };
+
+        Ok(Self {
+            name,
+            address,
+            netmask,
+            default,
+        })
+    }
+}
+
+/// LibCSP reads every value as a string, so numbers and booleans are accepted as such.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn optional(entry: &Mapping, key: &str) -> Result<Option<String>, String> {
+    match entry.get(key) {
+        None => Ok(None),
+        Some(value) => scalar(value)
+            .map(Some)
+            .ok_or_else(|| format!("`{}` must be a single value", key)),
+    }
+}
+
+fn required(entry: &Mapping, key: &str) -> Result<String, String> {
+    optional(entry, key)?.ok_or_else(|| format!("missing `{}`", key))
+}
+
+fn number<T: std::str::FromStr>(entry: &Mapping, key: &str) -> Result<Option<T>, String> {
+    match optional(entry, key)? {
+        None => Ok(None),
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("`{}` is not a valid number: `{}`", key, value)),
+    }
+}
@@ -0,0 +1,220 @@
+//! A ZMQ hub for `CspZmqConfig` and `CspZmqInterface` interfaces, like the `zmqproxy`
+//! program that comes with LibCSP.
+//!
+//! Interfaces publish to the subscribe endpoint of the proxy, and subscribe to its
+//! publish endpoint. Every message is the 2 byte next hop address, followed by the
+//! CSP header and the payload.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{CspError, CspErrorKind};
+
+/// How often the proxy checks whether it should stop, in milliseconds.
+const POLL_INTERVAL_MS: i64 = 100;
+
+type Capture = Box<dyn FnMut(&[u8]) + Send>;
+
+/// Forwards every message published by one interface to the interfaces subscribed to it.
+///
+/// ```no_run
+/// use libcsp::zmqproxy::CspZmqProxy;
+///
+/// let proxy = CspZmqProxy::new()
+///     .log_traffic(true)
+///     .spawn()
+///     .unwrap();
+/// ```
+pub struct CspZmqProxy {
+    subscribe_endpoint: String,
+    publish_endpoint: String,
+    log_traffic: bool,
+    capture: Option<Capture>,
+}
+
+impl Default for CspZmqProxy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CspZmqProxy {
+    /// A proxy on the LibCSP default ports, 6000 and 7000 on every network interface.
+    pub fn new() -> Self {
+        Self {
+            subscribe_endpoint: "tcp://*:6000".to_string(),
+            publish_endpoint: "tcp://*:7000".to_string(),
+            log_traffic: false,
+            capture: None,
+        }
+    }
+
+    /// Sets the endpoint interfaces publish their packets to.
+    pub fn subscribe_endpoint(self, endpoint: impl Into<String>) -> Self {
+        Self {
+            subscribe_endpoint: endpoint.into(),
+            ..self
+        }
+    }
+
+    /// Sets the endpoint interfaces subscribe to.
+    pub fn publish_endpoint(self, endpoint: impl Into<String>) -> Self {
+        Self {
+            publish_endpoint: endpoint.into(),
+            ..self
+        }
+    }
+
+    /// Logs every forwarded message to the `libcsp::zmqproxy` target, at debug level.
+    pub fn log_traffic(self, log_traffic: bool) -> Self {
+        Self {
+            log_traffic,
+            ..self
+        }
+    }
+
+    /// Calls `capture` with every forwarded message, on the proxy thread.
+    pub fn capture(self, capture: impl FnMut(&[u8]) + Send + 'static) -> Self {
+        Self {
+            capture: Some(Box::new(capture)),
+            ..self
+        }
+    }
+
+    /// Binds both endpoints and forwards messages on a new thread, until the
+    /// returned handle is stopped or dropped.
+    pub fn spawn(self) -> Result<CspZmqProxyHandle, CspError> {
+        let sockets = self.bind()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("csp-zmqproxy".to_string())
+                .spawn(move || {
+                    if let Err(e) = self.forward(sockets, &stop) {
+                        log::error!(target: "libcsp::zmqproxy", "{}", e);
+                    }
+                })
+                .expect("Failed to spawn ZMQ proxy thread")
+        };
+
+        Ok(CspZmqProxyHandle {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Binds both endpoints and forwards messages on this thread, until an error occurs.
+    pub fn run(self) -> Result<(), CspError> {
+        let sockets = self.bind()?;
+        self.forward(sockets, &AtomicBool::new(false))
+    }
+
+    fn bind(&self) -> Result<ProxySockets, CspError> {
+        let context = zmq::Context::new();
+        let bind = |kind, endpoint: &str| {
+            let socket = context.socket(kind).map_err(zmq_error)?;
+            socket.bind(endpoint).map_err(|e| CspError {
+                kind: CspErrorKind::Driver,
+                message: format!("ZMQ proxy failed to bind `{}`: {}", endpoint, e),
+            })?;
+            Ok::<_, CspError>(socket)
+        };
+
+        Ok(ProxySockets {
+            subscriber: bind(zmq::XSUB, &self.subscribe_endpoint)?,
+            publisher: bind(zmq::XPUB, &self.publish_endpoint)?,
+            _context: context,
+        })
+    }
+
+    fn forward(mut self, sockets: ProxySockets, stop: &AtomicBool) -> Result<(), CspError> {
+        let ProxySockets {
+            subscriber,
+            publisher,
+            ..
+        } = &sockets;
+
+        while !stop.load(Ordering::Relaxed) {
+            let mut items = [
+                subscriber.as_poll_item(zmq::POLLIN),
+                publisher.as_poll_item(zmq::POLLIN),
+            ];
+            zmq::poll(&mut items, POLL_INTERVAL_MS).map_err(zmq_error)?;
+
+            // Packets from the interfaces
+            if items[0].is_readable() {
+                let message = subscriber.recv_multipart(0).map_err(zmq_error)?;
+                for part in &message {
+                    self.inspect(part);
+                }
+                publisher.send_multipart(message, 0).map_err(zmq_error)?;
+            }
+
+            // Subscriptions from the interfaces
+            if items[1].is_readable() {
+                let message = publisher.recv_multipart(0).map_err(zmq_error)?;
+                subscriber.send_multipart(message, 0).map_err(zmq_error)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn inspect(&mut self, message: &[u8]) {
+        if self.log_traffic {
+            match message {
+                [high, low, frame @ ..] => log::debug!(
+                    target: "libcsp::zmqproxy",
+                    "{} bytes via {}",
+                    frame.len(),
+                    u16::from_be_bytes([*high, *low])
+                ),
+                _ => log::debug!(target: "libcsp::zmqproxy", "{} byte message", message.len()),
+            }
+        }
+        if let Some(capture) = &mut self.capture {
+            capture(message);
+        }
+    }
+}
+
+struct ProxySockets {
+    subscriber: zmq::Socket,
+    publisher: zmq::Socket,
+    // Dropped last
+    _context: zmq::Context,
+}
+
+fn zmq_error(e: zmq::Error) -> CspError {
+    CspError {
+        kind: CspErrorKind::Driver,
+        message: format!("ZMQ proxy: {}", e),
+    }
+}
+
+/// A proxy running on its own thread.
+pub struct CspZmqProxyHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CspZmqProxyHandle {
+    /// Stops forwarding and closes both endpoints.
+    pub fn stop(self) {}
+}
+
+impl Drop for CspZmqProxyHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
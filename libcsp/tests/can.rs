@@ -0,0 +1,65 @@
+#![cfg(feature = "socketcan")]
+
+//! Needs a virtual CAN device:
+//!
+//! ```bash
+//! sudo modprobe vcan
+//! sudo ip link add dev vcan0 type vcan
+//! sudo ip link set up vcan0
+//! cargo test -p libcsp --features socketcan --test can -- --ignored
+//! ```
+
+mod common;
+
+use libcsp::{
+    interface::CspCanInterface, CspConnAddress, CspConnPriority, CspPort, LibCspBuilder,
+    LibCspConfig,
+};
+use std::time::Duration;
+
+#[test]
+#[ignore]
+fn test_can_over_vcan() {
+    let address = 1;
+    let remote_address = 2;
+    let port = 10;
+
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+
+    // Both interfaces sit on the same bus
+    let (can_a, can_b) = common::add_looped_link(
+        &csp_instance,
+        remote_address,
+        CspCanInterface::new("vcan0")
+            .ifname("CAN_A")
+            .address(address),
+        CspCanInterface::new("vcan0")
+            .ifname("CAN_B")
+            .address(remote_address),
+    );
+
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(remote_address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    connection.send_packet(b"Hello over CAN").unwrap();
+
+    let conn = socket
+        .accept_timeout(Duration::from_secs(2))
+        .expect("No connection received over CAN");
+    let packet = conn
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("No packet received over CAN");
+    assert_eq!(packet.as_slice(), b"Hello over CAN");
+
+    assert_eq!(can_a.info().stats.tx, 1);
+    assert_eq!(can_b.info().stats.rx, 1);
+}
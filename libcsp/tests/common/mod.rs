@@ -0,0 +1,83 @@
+//! Helpers shared by the integration tests. Each test binary only uses some of them.
+#![allow(dead_code)]
+
+use libcsp::{
+    interface::{CspInterfaceHandle, InterfaceBuilder},
+    LibCspInstance, Route,
+};
+use std::{
+    ffi::OsStr,
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+/// Printed by a peer node once it is ready for traffic, see `spawn_peer`.
+pub const PEER_READY: &str = "csp-peer-ready";
+
+/// Kills the child process when the test ends, even if it fails.
+pub struct ChildGuard(pub Child);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Runs the ignored test `name` of this test binary as a peer node in its own process,
+/// with the environment variable `key` set so that it doesn't return straight away.
+///
+/// Returns once the peer has printed `PEER_READY`. Its stdout is drained until it
+/// exits, so its last writes never go to a closed pipe.
+pub fn spawn_peer(name: &str, key: &str, value: impl AsRef<OsStr>) -> ChildGuard {
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args([
+            name,
+            "--exact",
+            "--ignored",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(key, value)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn the peer node");
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let peer = ChildGuard(child);
+
+    // The test harness prints its own text around the output of the test, so the
+    // marker can share a line with it
+    let (ready, is_ready) = mpsc::channel();
+    thread::spawn(move || {
+        for line in stdout.lines().map_while(Result::ok) {
+            if line.contains(PEER_READY) {
+                let _ = ready.send(());
+            }
+        }
+    });
+    is_ready
+        .recv_timeout(Duration::from_secs(10))
+        .expect("The peer node exited before it was ready");
+    peer
+}
+
+/// Adds both ends of one link to this node, and routes `remote_address` through `local`.
+///
+/// `remote` must hold the remote address, so that packets sent to it leave through
+/// `local`, arrive on `remote`, and are delivered back to this node. It is added
+/// first, so it is ready by the time `local` starts.
+pub fn add_looped_link(
+    csp_instance: &LibCspInstance,
+    remote_address: u16,
+    local: impl InterfaceBuilder,
+    remote: impl InterfaceBuilder,
+) -> (CspInterfaceHandle, CspInterfaceHandle) {
+    let remote = csp_instance.add_interface(remote).unwrap();
+    let local = csp_instance
+        .add_interface_route(Route::new(remote_address), local)
+        .unwrap();
+    (local, remote)
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "serde")]
+
+use libcsp::{
+    interface::CspZmqConfig, CspConnOptions, CspDebugChannel, InterfaceKind, NodeConfig, Route,
+};
+use std::time::Duration;
+
+const NODE_TOML: &str = r#"
+address = 5
+hostname = "ground"
+service_timeout_ms = 250
+conn_dfl_so = "RDP | CRC32"
+debug_channels = ["error", "packet"]
+
+[[interfaces]]
+type = "zmq_endpoints"
+publish_endpoint = "tcp://127.0.0.1:6000"
+subscribe_endpoint = "tcp://127.0.0.1:7000"
+routes = [
+    { address = 0, netmask = 0 },
+    { address = 10, via = 12 },
+]
+"#;
+
+#[test]
+fn test_node_config_from_toml() {
+    let config: NodeConfig = toml::from_str(NODE_TOML).unwrap();
+    config.validate().unwrap();
+
+    assert_eq!(
+        config.debug_channels,
+        [CspDebugChannel::Error, CspDebugChannel::Packet]
+    );
+
+    let libcsp_config = config.libcsp_config();
+    assert_eq!(libcsp_config.address, 5);
+    assert_eq!(libcsp_config.hostname, "ground");
+    assert_eq!(libcsp_config.model, "{model unspecified}");
+    assert_eq!(libcsp_config.service_timeout, Duration::from_millis(250));
+    assert_eq!(
+        libcsp_config.conn_dfl_so,
+        CspConnOptions::RDP | CspConnOptions::CRC32
+    );
+
+    let interface = &config.interfaces[0];
+    let InterfaceKind::Zmq(zmq) = &interface.kind;
+    assert_eq!(zmq.publish_endpoint(), "tcp://127.0.0.1:6000");
+    assert_eq!(zmq.subscribe_endpoint(), "tcp://127.0.0.1:7000");
+    assert_eq!(Route::from(interface.routes[0]), Route::default_address());
+    assert_eq!(Route::from(interface.routes[1]), Route::new(10).via(12));
+}
+
+#[test]
+fn test_node_config_rejects_bad_input() {
+    // Unknown fields are most likely typos
+    assert!(toml::from_str::<NodeConfig>("address = 1\nhostnam = \"x\"").is_err());
+
+    let config: NodeConfig =
+        toml::from_str("address = 1\n[[interfaces]]\ntype = \"zmq\"\nhost = \"localhost\"")
+            .unwrap();
+    assert!(config.validate().is_err());
+
+    let config: NodeConfig = toml::from_str("address = 1\nconn_dfl_so = \"RDP | NORDP\"").unwrap();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_zmq_interface_config() {
+    let config: NodeConfig = toml::from_str(
+        r#"
+address = 1
+
+[[interfaces]]
+type = "zmq"
+host = "localhost"
+ifname = "HUB"
+rx_filter = [1, 2]
+routes = [{ address = 2 }]
+
+[[interfaces]]
+type = "zmq"
+publish_endpoint = "ipc:///tmp/csp-pub"
+subscribe_endpoint = "ipc:///tmp/csp-sub"
+routes = [{ address = 3 }]
+"#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+
+    let InterfaceKind::Zmq(hub) = &config.interfaces[0].kind;
+    assert_eq!(
+        *hub,
+        CspZmqConfig::host("localhost")
+            .ifname("HUB")
+            .rx_filter([1, 2])
+    );
+    let InterfaceKind::Zmq(sniffer) = &config.interfaces[1].kind;
+    assert_eq!(
+        *sniffer,
+        CspZmqConfig::endpoints("ipc:///tmp/csp-pub", "ipc:///tmp/csp-sub")
+    );
+
+    // Malformed endpoints are reported while loading, with the offending endpoint
+    let error = toml::from_str::<NodeConfig>(
+        "address = 1\n[[interfaces]]\ntype = \"zmq\"\npublish_endpoint = \"localhost:6000\"\nsubscribe_endpoint = \"tcp://localhost:7000\"",
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("`localhost:6000`"));
+
+    // Either a host or both endpoints
+    assert!(toml::from_str::<NodeConfig>(
+        "address = 1\n[[interfaces]]\ntype = \"zmq\"\npublish_endpoint = \"tcp://localhost:6000\""
+    )
+    .is_err());
+
+    // LibCSP has no driver flags, so there's no `promiscuous` key
+    assert!(toml::from_str::<NodeConfig>(
+        "address = 1\n[[interfaces]]\ntype = \"zmq\"\nhost = \"localhost\"\npromiscuous = true"
+    )
+    .is_err());
+}
@@ -1,46 +1,74 @@
 use libcsp::{
-    CspConnAddress, CspConnPriority, LibCspBuilder, LibCspConfig, CspPort
+    CspConnAddress, CspConnKind, CspConnPriority, CspConnState, LibCspBuilder, LibCspConfig,
+    CspPort,
 };
-use std::{thread, time::Duration};
+use std::time::Duration;
 
 #[test]
 fn test_loopback_connectivity() {
     let address = 1;
     let port = 10;
-    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
-
-    thread::scope(|s| {
-        // Server
-        s.spawn(|| {
-            let socket = csp_instance.open_server_socket(CspPort::port(port)).unwrap();
-            if let Some(conn) = socket.accept_timeout(Duration::from_secs(2)) {
-                if let Some(packet) = conn.iter_packets(Duration::from_secs(1)).next() {
-                    let data = String::from_utf8_lossy(packet.as_slice());
-                    let data = data.trim_end_matches('\0');
-                    assert_eq!(data, "Hello from test");
-                } else {
-                    panic!("No packet received");
-                }
-            } else {
-                panic!("No connection received");
-            }
-        });
-
-        // Client
-        s.spawn(|| {
-            thread::sleep(Duration::from_millis(100));
-            let client = csp_instance.client();
-            let connection = client
-                .connect(
-                    CspConnAddress::new(address, port),
-                    CspConnPriority::Normal,
-                    Duration::from_secs(1),
-                )
-                .unwrap();
-
-            connection
-                .send_packet(b"Hello from test")
-                .unwrap();
-        });
-    });
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address))
+        .manual_routing()
+        .build();
+
+    // Server
+    let socket = csp_instance.open_server_socket(CspPort::port(port)).unwrap();
+
+    // Client
+    let client = csp_instance.client();
+    let connection = client
+        .connect(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+    connection
+        .send_packet(b"Hello from test")
+        .unwrap();
+
+    // Move the packet from the loopback interface to the server socket
+    assert!(csp_instance.route_work());
+
+    // LibCSP holds the incoming connection, with its packet, until it is accepted
+    let connections = csp_instance.connections();
+    let pending = connections
+        .iter()
+        .find(|info| !info.held)
+        .expect("The incoming connection isn't listed");
+    assert_eq!(pending.kind, CspConnKind::Server);
+    assert_eq!(pending.state, CspConnState::Open);
+    assert_eq!(pending.rx_queue, 1);
+    assert_eq!(pending.age, None);
+
+    let conn = socket
+        .accept_timeout(Duration::ZERO)
+        .expect("No connection received");
+    let mut packets = conn.iter_packets(Duration::ZERO);
+    let packet = packets.next().expect("No packet received");
+
+    let data = String::from_utf8_lossy(packet.as_slice());
+    let data = data.trim_end_matches('\0');
+    assert_eq!(data, "Hello from test");
+
+    // Both ends of the loopback connection are in the table
+    let connections = csp_instance.connections();
+    assert_eq!(connections.len(), 2);
+    let client_info = connections
+        .iter()
+        .find(|info| info.kind == CspConnKind::Client)
+        .unwrap();
+    assert_eq!(client_info.state, CspConnState::Open);
+    assert_eq!(client_info.dst, CspConnAddress::new(address, port));
+    let server_info = connections
+        .iter()
+        .find(|info| info.kind == CspConnKind::Server)
+        .unwrap();
+    assert_eq!(server_info.dst, CspConnAddress::new(address, port));
+
+    drop(packets);
+    drop(connection);
+    assert!(csp_instance.connections().is_empty());
 }
@@ -0,0 +1,137 @@
+mod common;
+
+use libcsp::{
+    interface::{CspFrame, CspInterface, CspInterfaceRx, CspRustInterface},
+    CspConnAddress, CspConnPriority, CspError, CspId, CspPort, LibCspBuilder, LibCspConfig,
+};
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    time::Duration,
+};
+
+/// One end of a cable, frames transmitted on it are received on the other end.
+struct Wire {
+    rx: Arc<Mutex<Option<CspInterfaceRx>>>,
+    peer: Arc<Mutex<Option<CspInterfaceRx>>>,
+    sent: mpsc::Sender<(u16, CspId, Vec<u8>)>,
+}
+
+impl CspInterface for Wire {
+    fn start(&mut self, rx: CspInterfaceRx) {
+        *self.rx.lock().unwrap() = Some(rx);
+    }
+
+    fn transmit(&mut self, via: u16, frame: CspFrame<'_>) -> Result<(), CspError> {
+        self.sent
+            .send((via, frame.id(), frame.data().to_vec()))
+            .unwrap();
+        match *self.peer.lock().unwrap() {
+            Some(peer) => peer.receive_frame(frame.as_bytes()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_rust_interface() {
+    let address = 1;
+    let remote_address = 2;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address))
+        .manual_routing()
+        .build();
+
+    let rx_a = Arc::new(Mutex::new(None));
+    let rx_b = Arc::new(Mutex::new(None));
+    let (sent, sent_frames) = mpsc::channel();
+
+    // Each wire hands its frames to the other
+    let (wire_a, wire_b) = common::add_looped_link(
+        &csp_instance,
+        remote_address,
+        CspRustInterface::new(
+            "WIRE_A",
+            Wire {
+                rx: rx_a.clone(),
+                peer: rx_b.clone(),
+                sent: sent.clone(),
+            },
+        ),
+        CspRustInterface::new(
+            "WIRE_B",
+            Wire {
+                rx: rx_b.clone(),
+                peer: rx_a.clone(),
+                sent,
+            },
+        )
+        .address(remote_address),
+    );
+    assert_eq!(csp_instance.interface("WIRE_B"), Some(wire_b));
+    assert_eq!(rx_b.lock().unwrap().unwrap().interface(), wire_b);
+
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(remote_address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    connection.send_packet(b"Hello over Rust").unwrap();
+
+    let (via, id, data) = sent_frames.try_recv().expect("Nothing was transmitted");
+    assert_eq!(via, remote_address);
+    assert_eq!(id.dst, remote_address);
+    assert_eq!(id.dport, port);
+    assert_eq!(data, b"Hello over Rust");
+
+    assert!(csp_instance.route_work());
+    let conn = socket
+        .accept_timeout(Duration::ZERO)
+        .expect("No connection received over the Rust interface");
+    let packet = conn
+        .iter_packets(Duration::ZERO)
+        .next()
+        .expect("No packet received over the Rust interface");
+    assert_eq!(packet.as_slice(), b"Hello over Rust");
+
+    // Packets can also be injected from their header and payload
+    let rx = rx_a.lock().unwrap().unwrap();
+    let other_port = 11;
+    let other_socket = csp_instance
+        .open_server_socket(CspPort::port(other_port))
+        .unwrap();
+    rx.receive(
+        CspId {
+            priority: CspConnPriority::Normal,
+            flags: 0,
+            src: remote_address,
+            dst: address,
+            dport: other_port,
+            sport: 20,
+        },
+        b"Injected",
+    )
+    .unwrap();
+    assert!(csp_instance.route_work());
+    let conn = other_socket
+        .accept_timeout(Duration::ZERO)
+        .expect("Injected packet was not delivered");
+    assert_eq!(conn.src().address, remote_address);
+    let packet = conn.iter_packets(Duration::ZERO).next().unwrap();
+    assert_eq!(packet.as_slice(), b"Injected");
+
+    assert!(rx.receive_frame(&[]).is_err());
+    assert!(rx.receive(packet.id(), &[0; 4096]).is_err());
+
+    let stats = wire_a.info().stats;
+    assert_eq!(stats.tx, 1);
+    assert_eq!(stats.rx, 1);
+    assert_eq!(stats.rx_error, 1);
+    assert_eq!(stats.frame, 1);
+    assert_eq!(wire_b.info().stats.rx, 1);
+}
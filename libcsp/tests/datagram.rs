@@ -0,0 +1,94 @@
+use libcsp::{
+    interface::CspLossyLink, CspConnOptions, CspConnPriority, CspPort, CspSocketOptions,
+    LibCspBuilder, LibCspConfig, Route,
+};
+use std::time::Duration;
+
+#[test]
+fn test_datagram_socket() {
+    let address = 1;
+    let remote_address = 2;
+    let beacon_port = 20;
+    let ground_port = 21;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+
+    let link = CspLossyLink::new(Default::default());
+    csp_instance
+        .add_interface_route(Route::new(remote_address), link.end_a("LINK"))
+        .unwrap();
+    csp_instance
+        .add_interface(link.end_b("REMOTE").address(remote_address))
+        .unwrap();
+
+    let ground = csp_instance
+        .open_datagram_socket(CspPort::port(ground_port))
+        .unwrap();
+    let beacons = csp_instance
+        .open_datagram_socket(CspPort::port(beacon_port))
+        .unwrap();
+    assert_eq!(beacons.port(), CspPort::port(beacon_port));
+
+    // A port can only be bound once
+    assert!(csp_instance
+        .open_datagram_socket(CspPort::port(beacon_port))
+        .is_err());
+    assert!(csp_instance
+        .open_datagram_socket_opts(CspPort::port(22), CspSocketOptions::RDPREQ)
+        .is_err());
+
+    // Nothing arrives before anything is sent
+    assert!(beacons.recv_from(Duration::from_millis(50)).is_none());
+
+    for number in 0..3u8 {
+        ground
+            .send_to(
+                remote_address,
+                beacon_port,
+                &[b'B', number],
+                CspConnPriority::High,
+            )
+            .unwrap();
+    }
+    for number in 0..3u8 {
+        let (packet, id) = beacons
+            .recv_from(Duration::from_secs(1))
+            .expect("No beacon received");
+        assert_eq!(packet.as_slice(), &[b'B', number]);
+        assert_eq!(id.src, address);
+        assert_eq!(id.dst, remote_address);
+        assert_eq!(id.sport, ground_port);
+        assert_eq!(id.dport, beacon_port);
+        assert_eq!(id.priority, CspConnPriority::High);
+    }
+    assert_eq!(link.stats().delivered, 3);
+
+    // Replies go back to where the packet came from
+    beacons
+        .send_to(address, ground_port, b"ack", CspConnPriority::Normal)
+        .unwrap();
+    let (packet, id) = ground
+        .recv_from(Duration::from_secs(1))
+        .expect("No reply received");
+    assert_eq!(packet.as_slice(), b"ack");
+    assert_eq!((id.sport, id.dport), (beacon_port, ground_port));
+
+    // RDP needs a connection, and oversized packets never leave
+    assert!(ground
+        .send_to_opts(
+            remote_address,
+            beacon_port,
+            b"reliable",
+            CspConnPriority::Normal,
+            CspConnOptions::RDP,
+        )
+        .is_err());
+    assert!(ground
+        .send_to(
+            remote_address,
+            beacon_port,
+            &vec![0; 100_000],
+            CspConnPriority::Normal,
+        )
+        .is_err());
+    assert!(beacons.recv_from(Duration::from_millis(50)).is_none());
+}
@@ -0,0 +1,81 @@
+use libcsp::{
+    CspConnAddress, CspConnPriority, CspDebugChannel, CspPort, LibCspBuilder, LibCspConfig,
+};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{sync::Mutex, time::Duration};
+
+/// Keeps every record, as (target, level, message).
+struct Recorder(Mutex<Vec<(String, Level, String)>>);
+
+impl Log for Recorder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push((
+            record.target().to_string(),
+            record.level(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDER: Recorder = Recorder(Mutex::new(Vec::new()));
+
+#[test]
+fn test_debug_forwarding() {
+    log::set_logger(&RECORDER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let address = 1;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address))
+        .manual_routing()
+        .debug_channels(&[CspDebugChannel::Packet])
+        .build();
+
+    // LibCSP traces the packet when it is sent, and when it is routed
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    connection.send_packet(b"traced").unwrap();
+    assert!(csp_instance.route_work());
+    assert!(socket.accept_timeout(Duration::ZERO).is_some());
+
+    let records = RECORDER.0.lock().unwrap();
+    let packets: Vec<_> = records
+        .iter()
+        .filter(|(target, _, _)| target == "libcsp::packet")
+        .collect();
+    assert!(!packets.is_empty(), "{:?}", records);
+    for (_, level, message) in packets {
+        assert_eq!(*level, Level::Debug);
+        assert!(
+            message.starts_with("INP:") || message.starts_with("OUT:"),
+            "{:?}",
+            message
+        );
+        // The colour codes LibCSP prints are stripped
+        assert!(!message.contains('\x1b'), "{:?}", message);
+    }
+
+    // Only the selected channel is forwarded
+    assert!(
+        records
+            .iter()
+            .all(|(target, _, _)| target == "libcsp::packet"),
+        "{:?}",
+        records
+    );
+}
@@ -0,0 +1,43 @@
+use libcsp::{CspConnAddress, CspConnPriority, CspPort, LibCspBuilder, LibCspConfig};
+use std::time::Duration;
+
+#[test]
+fn test_loopback_interface_counters() {
+    let address = 1;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address))
+        .manual_routing()
+        .build();
+
+    let _socket = csp_instance.open_server_socket(CspPort::port(port)).unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+    csp_instance.reset_all_interface_counters();
+    connection.send_packet(b"Hello from test").unwrap();
+    assert!(csp_instance.route_work());
+
+    let loopback = || {
+        csp_instance
+            .interfaces()
+            .into_iter()
+            .find(|info| info.name == "LOOP")
+            .expect("No loopback interface")
+    };
+
+    let info = loopback();
+    assert_eq!(info.stats.tx, 1);
+    assert!(info.stats.txbytes > 0);
+    assert!(info.mtu > 0);
+
+    csp_instance.reset_interface_counters("LOOP").unwrap();
+    assert_eq!(loopback().stats.tx, 0);
+
+    assert!(csp_instance.reset_interface_counters("NOPE").is_err());
+}
@@ -1,42 +1,37 @@
-use std::process::{Command, Child};
+#![cfg(feature = "zmqproxy")]
+
+mod common;
+
+use common::ChildGuard;
+use std::process::Command;
 use std::thread;
 use std::time::Duration;
 use libcsp::{
     CspConnAddress, CspConnPriority, LibCspBuilder, LibCspConfig, interface::CspZmqInterface, Route
 };
 
-struct ChildGuard(Child);
-
-impl Drop for ChildGuard {
-    fn drop(&mut self) {
-        let _ = self.0.kill();
-        let _ = self.0.wait();
-    }
-}
-
 #[test]
 fn test_c_server_interop() {
-    // 1. Compile ZMQ Hub
-    Command::new("gcc")
-        .args(["tests/c_src/zmq_hub.c", "-o", "../target/zmq_hub", "-lzmq"])
-        .status()
-        .expect("Failed to compile ZMQ Hub");
-
-    // 2. Compile C server
+    // 1. Compile C server
     Command::new("gcc")
         .args(["tests/c_src/simple_server.c", "-o", "../target/c_server", "-lcsp", "-lzmq", "-lpthread"])
         .status()
         .expect("Failed to compile C server");
 
-    // 3. Spawn ZMQ Hub
-    let _zmq_hub = ChildGuard(Command::new("../target/zmq_hub").spawn().expect("Failed to spawn ZMQ Hub"));
+    // 2. Spawn ZMQ Hub
+    let _zmq_hub = ChildGuard(
+        Command::new(env!("CARGO_BIN_EXE_zmqproxy"))
+            .args(["--subscribe", "tcp://127.0.0.1:6000", "--publish", "tcp://127.0.0.1:7000"])
+            .spawn()
+            .expect("Failed to spawn ZMQ Hub"),
+    );
     thread::sleep(Duration::from_secs(1));
 
-    // 4. Spawn C process
+    // 3. Spawn C process
     let mut c_proc = Command::new("../target/c_server").spawn().expect("Failed to spawn C server");
     thread::sleep(Duration::from_secs(1));
 
-    // 5. Connect using Rust wrapper
+    // 4. Connect using Rust wrapper
     let address = 1;
     let server_address = 10;
     let server_port = 10;
@@ -69,7 +64,7 @@ fn test_c_server_interop() {
         .send_packet(b"Hello from Rust")
         .expect("Failed to send packet");
 
-    // 6. Wait for C process to finish
+    // 5. Wait for C process to finish
     let status = c_proc.wait().expect("C process failed");
     assert!(status.success());
 }
@@ -0,0 +1,140 @@
+use libcsp::{
+    interface::{CspLossyLink, CspLossyLinkConfig},
+    CspConnAddress, CspConnPriority, CspPort, LibCspBuilder, LibCspConfig, LibCspInstance, Route,
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+const PACKETS: usize = 100;
+
+/// Sends `PACKETS` numbered packets to `remote_address:port`, and returns the numbers
+/// received and how long it took until the last one arrived.
+fn send_over(csp_instance: &LibCspInstance, remote_address: u16, port: u8) -> (Vec<u8>, Duration) {
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(remote_address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    let started = Instant::now();
+
+    // Received while sending, the connection queue only holds a handful of packets
+    let sender = thread::spawn(move || {
+        for number in 0..PACKETS {
+            connection.send_packet(&[number as u8]).unwrap();
+            // Paced, so that the buffer pool isn't used up by packets in flight
+            thread::sleep(Duration::from_millis(2));
+        }
+        connection
+    });
+
+    let conn = socket
+        .accept_timeout(Duration::from_secs(2))
+        .expect("No packet made it over the link");
+    let mut received = Vec::new();
+    let mut last = started;
+    for packet in conn.iter_packets(Duration::from_millis(500)) {
+        received.push(packet[0]);
+        last = Instant::now();
+    }
+    let _connection = sender.join().unwrap();
+    (received, last - started)
+}
+
+#[test]
+fn test_lossy_link() {
+    let address = 1;
+    // Duplicates are only visible with deduplication off
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address).dedup(0)).build();
+
+    // Lost packets never arrive, and the same seed loses the same packets
+    let lossy = CspLossyLinkConfig::default().drop_probability(0.3).seed(7);
+    let mut runs = Vec::new();
+    for (remote_address, port) in [(2, 10), (3, 11)] {
+        let link = CspLossyLink::new(lossy.clone());
+        csp_instance
+            .add_interface_route(
+                Route::new(remote_address),
+                link.end_a(format!("LOSSY_{}", remote_address)),
+            )
+            .unwrap();
+        csp_instance
+            .add_interface(
+                link.end_b(format!("REMOTE_{}", remote_address))
+                    .address(remote_address),
+            )
+            .unwrap();
+
+        let (received, _) = send_over(&csp_instance, remote_address, port);
+        let stats = link.stats();
+        assert_eq!(stats.sent, PACKETS as u64);
+        assert!(stats.dropped > 0 && stats.dropped < PACKETS as u64);
+        assert_eq!(stats.delivered, PACKETS as u64 - stats.dropped);
+        assert_eq!(received.len() as u64, stats.delivered);
+        runs.push(received);
+    }
+    assert_eq!(runs[0], runs[1]);
+
+    // Latency, duplication and reordering
+    let link = CspLossyLink::new(
+        CspLossyLinkConfig::default()
+            .latency(Duration::from_millis(50))
+            .reorder_probability(0.2)
+            .duplicate_probability(0.1)
+            .seed(1),
+    );
+    csp_instance
+        .add_interface_route(Route::new(4), link.end_a("SLOW"))
+        .unwrap();
+    csp_instance
+        .add_interface(link.end_b("SLOW_REMOTE").address(4))
+        .unwrap();
+
+    let (received, elapsed) = send_over(&csp_instance, 4, 12);
+    assert!(elapsed >= Duration::from_millis(50));
+    let stats = link.stats();
+    assert!(stats.duplicated > 0);
+    assert_eq!(received.len() as u64, PACKETS as u64 + stats.duplicated);
+    assert!(stats.reordered > 0);
+    assert!(received.windows(2).any(|pair| pair[0] > pair[1]));
+
+    // Jitter moves packets either side of the latency, and loses none of them
+    let link = CspLossyLink::new(
+        CspLossyLinkConfig::default()
+            .latency(Duration::from_millis(10))
+            .jitter(Duration::from_millis(20))
+            .seed(2),
+    );
+    csp_instance
+        .add_interface_route(Route::new(6), link.end_a("JITTER"))
+        .unwrap();
+    csp_instance
+        .add_interface(link.end_b("JITTER_REMOTE").address(6))
+        .unwrap();
+
+    let (mut received, _) = send_over(&csp_instance, 6, 14);
+    assert_eq!(link.stats().delivered, PACKETS as u64);
+    received.sort_unstable();
+    assert_eq!(received, (0..PACKETS as u8).collect::<Vec<_>>());
+
+    // A bandwidth cap spreads the packets out, but keeps them in order
+    let link = CspLossyLink::new(CspLossyLinkConfig::default().bandwidth(2_000));
+    csp_instance
+        .add_interface_route(Route::new(5), link.end_a("NARROW"))
+        .unwrap();
+    csp_instance
+        .add_interface(link.end_b("NARROW_REMOTE").address(5))
+        .unwrap();
+
+    let (received, elapsed) = send_over(&csp_instance, 5, 13);
+    assert_eq!(received, (0..PACKETS as u8).collect::<Vec<_>>());
+    // Every frame is at least a 4 byte header and one byte of data
+    assert!(elapsed >= Duration::from_millis(PACKETS as u64 * 5 * 1000 / 2_000));
+}
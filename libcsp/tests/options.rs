@@ -0,0 +1,90 @@
+use libcsp::{
+    CspConnAddress, CspConnOptions, CspConnPriority, CspPort, CspSocketOptions, LibCspBuilder,
+    LibCspConfig,
+};
+use std::time::Duration;
+
+#[test]
+fn test_conn_options_conflicts() {
+    assert!(CspConnOptions::empty().check().is_ok());
+    assert!(
+        (CspConnOptions::RDP | CspConnOptions::HMAC | CspConnOptions::CRC32)
+            .check()
+            .is_ok()
+    );
+    assert!((CspConnOptions::NORDP | CspConnOptions::NOCRC32)
+        .check()
+        .is_ok());
+
+    assert!((CspConnOptions::RDP | CspConnOptions::NORDP)
+        .check()
+        .is_err());
+    assert!((CspConnOptions::HMAC | CspConnOptions::NOHMAC)
+        .check()
+        .is_err());
+    assert!(
+        (CspConnOptions::CRC32 | CspConnOptions::NOCRC32 | CspConnOptions::SAME)
+            .check()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_socket_options_conflicts() {
+    assert!((CspSocketOptions::RDPREQ | CspSocketOptions::HMACREQ)
+        .check()
+        .is_ok());
+    assert!((CspSocketOptions::CRC32REQ | CspSocketOptions::HMACPROHIB)
+        .check()
+        .is_ok());
+
+    assert!((CspSocketOptions::RDPREQ | CspSocketOptions::RDPPROHIB)
+        .check()
+        .is_err());
+    assert!((CspSocketOptions::HMACREQ | CspSocketOptions::HMACPROHIB)
+        .check()
+        .is_err());
+    assert!((CspSocketOptions::CRC32REQ | CspSocketOptions::CRC32PROHIB)
+        .check()
+        .is_err());
+}
+
+#[test]
+fn test_socket_requires_rdp() {
+    let address = 1;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+    let socket = csp_instance
+        .open_server_socket_opts(CspPort::port(port), CspSocketOptions::RDPREQ)
+        .unwrap();
+    let client = csp_instance.client();
+
+    // LibCSP drops the packets of a connection without RDP
+    let plain = client
+        .connect(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    plain.send_packet(b"plain").unwrap();
+    assert!(socket.accept_timeout(Duration::from_millis(200)).is_none());
+
+    let rdp = client
+        .connect_opts(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+            CspConnOptions::RDP,
+        )
+        .unwrap();
+    rdp.send_packet(b"rdp").unwrap();
+    let conn = socket
+        .accept_timeout(Duration::from_secs(1))
+        .expect("The RDP connection wasn't accepted");
+    let packet = conn
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("No packet received");
+    assert_eq!(packet.as_slice(), b"rdp");
+}
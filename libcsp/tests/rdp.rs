@@ -0,0 +1,114 @@
+use libcsp::{
+    interface::{CspLossyLink, CspLossyLinkConfig},
+    CspConnAddress, CspConnOptions, CspConnPriority, CspPort, CspSocketOptions, LibCspBuilder,
+    LibCspConfig, RdpConfig, Route,
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+const PACKETS: u8 = 60;
+
+#[test]
+fn test_rdp_over_lossy_link() {
+    let address = 1;
+    let remote_address = 2;
+    let port = 10;
+
+    // An invalid config is returned as an error, and doesn't use up the instance
+    let invalid_rdp = RdpConfig::default().window_size(0);
+    assert!(
+        LibCspBuilder::new(LibCspConfig::new(address).rdp(invalid_rdp))
+            .try_build()
+            .is_err()
+    );
+
+    let instance_rdp = RdpConfig::default().window_size(3);
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address).rdp(instance_rdp)).build();
+    assert_eq!(csp_instance.rdp_config(), instance_rdp);
+
+    // Parameters LibCSP can't use are rejected before they reach it
+    for invalid in [
+        RdpConfig::default().window_size(0),
+        RdpConfig::default().window_size(1000),
+        RdpConfig::default().conn_timeout(Duration::ZERO),
+        RdpConfig::default().packet_timeout(Duration::from_secs(20)),
+        RdpConfig::default().ack_timeout(Duration::from_secs(2)),
+        RdpConfig::default().ack_delay_count(10),
+    ] {
+        assert!(csp_instance.set_rdp_config(&invalid).is_err());
+    }
+    assert!(RdpConfig::default()
+        .delayed_acks(false)
+        .ack_timeout(Duration::from_secs(2))
+        .check()
+        .is_ok());
+    assert_eq!(csp_instance.rdp_config(), instance_rdp);
+
+    // A third of the packets towards the remote address are lost
+    let link = CspLossyLink::new(CspLossyLinkConfig::default().drop_probability(0.3).seed(3));
+    csp_instance
+        .add_interface_route(Route::new(remote_address), link.end_a("LOSSY"))
+        .unwrap();
+    csp_instance
+        .add_interface(link.end_b("REMOTE").address(remote_address))
+        .unwrap();
+    let socket = csp_instance
+        .open_server_socket_opts(CspPort::port(port), CspSocketOptions::RDPREQ)
+        .unwrap();
+
+    // Quick retransmissions, so the test doesn't wait for the LibCSP defaults
+    let rdp = RdpConfig::default()
+        .window_size(5)
+        .conn_timeout(Duration::from_secs(2))
+        .packet_timeout(Duration::from_millis(100))
+        .ack_timeout(Duration::from_millis(20))
+        .ack_delay_count(2);
+
+    // The handshake itself can be lost, and is simply tried again
+    let deadline = Instant::now() + Duration::from_secs(20);
+    let connection = loop {
+        assert!(
+            Instant::now() < deadline,
+            "The RDP handshake never got through"
+        );
+        if let Ok(connection) = csp_instance.client().connect_rdp(
+            CspConnAddress::new(remote_address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+            CspConnOptions::empty(),
+            &rdp,
+        ) {
+            break connection;
+        }
+    };
+    // Only the one connection got the parameters
+    assert_eq!(csp_instance.rdp_config(), instance_rdp);
+
+    let sender = thread::spawn(move || {
+        for number in 0..PACKETS {
+            // Blocks while the window is full
+            connection.send_packet(&[number]).unwrap();
+        }
+        connection
+    });
+
+    let conn = socket
+        .accept_timeout(Duration::from_secs(5))
+        .expect("No RDP connection was accepted");
+    assert!(csp_instance
+        .connections()
+        .iter()
+        .all(|info| info.opts.contains(CspConnOptions::RDP)));
+    let received: Vec<u8> = conn
+        .iter_packets(Duration::from_secs(2))
+        .take(PACKETS as usize)
+        .map(|packet| packet[0])
+        .collect();
+    let _connection = sender.join().unwrap();
+
+    // Every packet arrives once and in order, even though some were lost on the way
+    assert_eq!(received, (0..PACKETS).collect::<Vec<_>>());
+    assert!(link.stats().dropped > 0);
+}
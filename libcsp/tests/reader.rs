@@ -0,0 +1,72 @@
+use libcsp::{
+    CspConnAddress, CspConnOptions, CspConnPriority, CspConnState, CspPort, LibCspBuilder,
+    LibCspConfig,
+};
+use std::{
+    io::{ErrorKind, Read},
+    time::{Duration, Instant},
+};
+
+#[test]
+fn test_reader_timeout_and_close() {
+    let address = 1;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+
+    // RDP tells the server when the client closes the connection
+    let connection = csp_instance
+        .client()
+        .connect_opts(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+            CspConnOptions::RDP,
+        )
+        .unwrap();
+    assert!(connection.is_active());
+    connection.send_packet(b"hello").unwrap();
+
+    let conn = socket
+        .accept_timeout(Duration::from_secs(1))
+        .expect("No connection accepted");
+    let mut reader = conn.into_reader(Duration::from_millis(100));
+    let mut buffer = [0; 5];
+    reader.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer, b"hello");
+
+    // A slow sender is not the end of the stream, and the read can be tried again
+    let error = reader.read(&mut buffer).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::TimedOut);
+    assert!(reader.connection().is_active());
+
+    connection.send_packet(b"world").unwrap();
+    reader.set_timeout(Duration::from_secs(1));
+    assert_eq!(reader.read(&mut buffer).unwrap(), 5);
+    assert_eq!(&buffer, b"world");
+
+    // Data sent before the close is still read, then the stream ends
+    connection.send_packet(b"bye").unwrap();
+    drop(connection);
+
+    reader.set_timeout(Duration::from_millis(100));
+    let mut rest = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        assert!(
+            Instant::now() < deadline,
+            "The close never reached the reader"
+        );
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => rest.extend_from_slice(&buffer[..read]),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::TimedOut),
+        }
+    }
+    assert_eq!(rest, b"bye");
+    assert_eq!(reader.connection().state(), CspConnState::Closed);
+    assert!(!reader.connection().is_active());
+    assert_eq!(reader.read(&mut buffer).unwrap(), 0);
+}
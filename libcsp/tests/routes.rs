@@ -0,0 +1,69 @@
+use libcsp::{
+    interface::{CspLossyLink, CspLossyLinkConfig},
+    LibCspBuilder, LibCspConfig, Route,
+};
+
+#[test]
+fn test_route_table_management() {
+    let address = 1;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address))
+        .manual_routing()
+        .build();
+
+    let (route, interface) = csp_instance.find_route(address).expect("No loopback route");
+    assert_eq!(route.address, address);
+    assert_eq!(interface, "LOOP");
+    assert!(csp_instance.find_route(5).is_none());
+
+    assert_eq!(csp_instance.load_routes("5/14 LOOP, 8/12 LOOP 5").unwrap(), 2);
+    assert!(csp_instance.load_routes("5/14 NO_SUCH_INTERFACE").is_err());
+
+    let (route, interface) = csp_instance.find_route(9).unwrap();
+    assert_eq!(route, Route::new(8).netmask_bits(12).via(5));
+    assert_eq!(interface, "LOOP");
+
+    let saved = csp_instance.save_routes().unwrap();
+    assert!(saved.contains("5/14 LOOP"), "{}", saved);
+    assert!(saved.contains("8/12 LOOP 5"), "{}", saved);
+
+    csp_instance.remove_route(Route::new(5)).unwrap();
+    assert!(csp_instance.find_route(5).is_none());
+    assert!(csp_instance.find_route(9).is_some());
+    assert!(csp_instance.remove_route(Route::new(5)).is_err());
+
+    csp_instance.clear_routes();
+    let routes = csp_instance.routes();
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].0.address, address);
+
+    // Several routes can share one interface
+    let loopback = csp_instance.interface("LOOP").unwrap();
+    csp_instance.add_route(Route::new(5), &loopback).unwrap();
+    csp_instance.add_route(Route::new(6), &loopback).unwrap();
+    assert_eq!(csp_instance.routes().len(), 3);
+    assert!(csp_instance.interface("NO_SUCH_INTERFACE").is_none());
+
+    csp_instance.set_default_interface(&loopback).unwrap();
+    assert!(loopback.info().is_default);
+    let (route, interface) = csp_instance.find_route(100).unwrap();
+    assert_eq!(route, Route::default_address());
+    assert_eq!(interface, "LOOP");
+
+    // Routes longer than the first guess at their saved length are all saved
+    let name = "A_RATHER_LONG_INTERFACE_NAME_".repeat(4);
+    let link = CspLossyLink::new(CspLossyLinkConfig::default());
+    let long = csp_instance
+        .add_interface(link.end_a(name.as_str()))
+        .unwrap();
+    for address in 20..30 {
+        csp_instance.add_route(Route::new(address), &long).unwrap();
+    }
+    let saved = csp_instance.save_routes().unwrap();
+    for address in 20..30 {
+        assert!(
+            saved.contains(&format!("{}/14 {}", address, name)),
+            "{}",
+            saved
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use libcsp::{CspConnAddress, CspConnPriority, CspErrorKind, CspPort, LibCspBuilder, LibCspConfig};
+use std::time::Duration;
+
+#[test]
+fn test_sfp() {
+    let address = 1;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+
+    // Several times the size of a single buffer, but within the connection queue
+    let image: Vec<u8> = (0..2_000u32).map(|i| (i % 251) as u8).collect();
+
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+    // Fragments that don't fit a buffer with the SFP header are refused
+    assert!(matches!(
+        connection.send_sfp(&image, 0, Duration::from_secs(1)),
+        Err(e) if matches!(e.kind, CspErrorKind::Inval)
+    ));
+    assert!(connection
+        .send_sfp(&image, 100_000, Duration::from_secs(1))
+        .is_err());
+
+    connection
+        .send_sfp(&image, 200, Duration::from_secs(1))
+        .unwrap();
+    let conn = socket
+        .accept_timeout(Duration::from_secs(1))
+        .expect("No connection for the SFP message");
+    assert_eq!(conn.recv_sfp(Duration::from_secs(1)).unwrap(), image);
+
+    // A small message still goes through SFP, in a single fragment
+    connection
+        .send_sfp(b"small", 100, Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(conn.recv_sfp(Duration::from_secs(1)).unwrap(), b"small");
+
+    // Nothing more was sent
+    let error = conn.recv_sfp(Duration::from_millis(100)).unwrap_err();
+    assert!(matches!(error.kind, CspErrorKind::Timedout));
+}
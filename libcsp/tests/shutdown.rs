@@ -0,0 +1,28 @@
+use libcsp::{CspConnAddress, CspConnPriority, CspPort, LibCspBuilder, LibCspConfig};
+use std::time::Duration;
+
+#[test]
+fn test_shutdown_with_open_handles() {
+    let address = 1;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+
+    let socket = csp_instance.open_server_socket(CspPort::port(port)).unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+    csp_instance.shutdown();
+
+    // The socket was closed by the shutdown, so nothing can be accepted anymore
+    assert!(socket.accept_timeout(Duration::from_millis(100)).is_none());
+
+    // Dropping the handles after the shutdown must not close them a second time
+    drop(connection);
+    drop(socket);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use libcsp::{
+    interface::CspTcpInterface, CspConnAddress, CspConnPriority, CspPort, LibCspBuilder,
+    LibCspConfig,
+};
+use std::{
+    io,
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Forwards every connection on `listener` to `target`, and lets the test cut them.
+#[derive(Clone, Default)]
+struct Proxy {
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Proxy {
+    fn spawn(listener: TcpListener, target: String) -> Self {
+        let proxy = Self::default();
+        let streams = proxy.streams.clone();
+        thread::spawn(move || {
+            for client in listener.incoming() {
+                let client = client.unwrap();
+                let server = TcpStream::connect(&target).unwrap();
+                streams
+                    .lock()
+                    .unwrap()
+                    .extend([client.try_clone().unwrap(), server.try_clone().unwrap()]);
+                pump(client.try_clone().unwrap(), server.try_clone().unwrap());
+                pump(server, client);
+            }
+        });
+        proxy
+    }
+
+    /// Drops every forwarded connection, as if the link went down.
+    fn cut(&self) {
+        for stream in self.streams.lock().unwrap().drain(..) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn pump(mut from: TcpStream, mut to: TcpStream) {
+    thread::spawn(move || {
+        let _ = io::copy(&mut from, &mut to);
+        let _ = to.shutdown(Shutdown::Both);
+    });
+}
+
+#[test]
+fn test_tcp_reconnect() {
+    let address = 1;
+    let remote_address = 2;
+    let port = 10;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+
+    // TCP_CLIENT connects to TCP_SERVER through the proxy
+    let server_address = "127.0.0.1:24611";
+    let proxy_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_address = proxy_listener.local_addr().unwrap().to_string();
+    let proxy = Proxy::spawn(proxy_listener, server_address.to_string());
+    let (client, _) = common::add_looped_link(
+        &csp_instance,
+        remote_address,
+        CspTcpInterface::connect(proxy_address)
+            .ifname("TCP_CLIENT")
+            .reconnect_interval(Duration::from_millis(50)),
+        CspTcpInterface::listen(server_address)
+            .ifname("TCP_SERVER")
+            .address(remote_address),
+    );
+
+    // A port in use is reported when the interface is added
+    assert!(csp_instance
+        .add_interface(CspTcpInterface::listen(server_address).ifname("TCP_USED"))
+        .is_err());
+    assert!(csp_instance
+        .add_interface(CspTcpInterface::connect("not an address"))
+        .is_err());
+
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(remote_address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+    // Packets are dropped until the client has connected
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let conn = loop {
+        assert!(Instant::now() < deadline, "The TCP client never connected");
+        connection.send_packet(b"first").unwrap();
+        if let Some(conn) = socket.accept_timeout(Duration::from_millis(100)) {
+            break conn;
+        }
+    };
+    let mut packets = conn.iter_packets(Duration::from_millis(100));
+    assert_eq!(packets.next().unwrap().as_slice(), b"first");
+
+    proxy.cut();
+
+    // The client connects again by itself
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        assert!(
+            Instant::now() < deadline,
+            "The TCP client never reconnected"
+        );
+        connection.send_packet(b"again").unwrap();
+        // Skips any duplicates of the first packet from the connecting loop
+        if packets.any(|packet| packet.as_slice() == b"again") {
+            break;
+        }
+    }
+    assert_eq!(client.name(), "TCP_CLIENT");
+}
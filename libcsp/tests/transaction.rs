@@ -0,0 +1,90 @@
+use libcsp::{
+    CspConnAddress, CspConnOptions, CspConnPriority, CspErrorKind, CspPort, LibCspBuilder,
+    LibCspConfig, LibCspInstance,
+};
+use std::{sync::mpsc, thread, time::Duration};
+
+const SERVER_PORT: u8 = 10;
+const SILENT_PORT: u8 = 11;
+
+/// Replies to every request with the request in upper case, until no connection
+/// arrives for a while.
+fn server(csp_instance: &LibCspInstance, ready: mpsc::Sender<()>) {
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(SERVER_PORT))
+        .unwrap();
+    ready.send(()).unwrap();
+
+    while let Some(conn) = socket.accept_timeout(Duration::from_secs(2)) {
+        while let Some(packet) = conn.read_packet(Duration::from_millis(200)) {
+            let _ = conn.send_packet(&packet.to_ascii_uppercase());
+        }
+    }
+}
+
+#[test]
+fn test_transactions() {
+    let address = 1;
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+    let _silent = csp_instance
+        .open_server_socket(CspPort::port(SILENT_PORT))
+        .unwrap();
+
+    thread::scope(|s| {
+        let (ready, wait_ready) = mpsc::channel();
+        s.spawn(|| server(&csp_instance, ready));
+        wait_ready.recv().unwrap();
+
+        let client = csp_instance.client();
+        let server_address = CspConnAddress::new(address, SERVER_PORT);
+        let transaction = |request: &[u8], reply_len: Option<usize>| {
+            client.transaction(
+                server_address,
+                CspConnPriority::Normal,
+                request,
+                reply_len,
+                Duration::from_secs(1),
+                CspConnOptions::empty(),
+            )
+        };
+
+        assert_eq!(transaction(b"hello", None).unwrap(), b"HELLO");
+        assert_eq!(transaction(b"hello", Some(5)).unwrap(), b"HELLO");
+        let error = transaction(b"hello", Some(4)).unwrap_err();
+        assert!(matches!(error.kind, CspErrorKind::ReplyLength));
+
+        // A request without a reply returns as soon as it is sent
+        assert!(transaction(b"no reply", Some(0)).unwrap().is_empty());
+
+        // Nobody answers on the silent port
+        let error = client
+            .transaction(
+                CspConnAddress::new(address, SILENT_PORT),
+                CspConnPriority::Normal,
+                b"hello?",
+                None,
+                Duration::from_millis(200),
+                CspConnOptions::empty(),
+            )
+            .unwrap_err();
+        assert!(matches!(error.kind, CspErrorKind::Timedout));
+
+        // Several transactions on one connection
+        let connection = client
+            .connect(
+                server_address,
+                CspConnPriority::Normal,
+                Duration::from_secs(1),
+            )
+            .unwrap();
+        for request in [&b"first"[..], b"second"] {
+            let reply = client
+                .transaction_persistent(&connection, request, None, Duration::from_secs(1))
+                .unwrap();
+            assert_eq!(reply, request.to_ascii_uppercase());
+        }
+
+        let error = transaction(&vec![0; 100_000], None).unwrap_err();
+        assert!(matches!(error.kind, CspErrorKind::Inval));
+    });
+}
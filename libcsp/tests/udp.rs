@@ -0,0 +1,141 @@
+#![cfg(feature = "udp")]
+
+//! Two nodes, each in its own process, linked over UDP on localhost.
+//! The peer node is this test binary again, running `udp_peer`.
+
+mod common;
+
+use libcsp::{
+    interface::CspUdpInterface, CspConnAddress, CspConnPriority, CspPort, LibCspBuilder,
+    LibCspConfig, Route,
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+const PEER_ENV: &str = "LIBCSP_UDP_PEER";
+
+const ADDRESS: u16 = 1;
+const PEER_ADDRESS: u16 = 2;
+const UDP_PORT: u16 = 24601;
+const PEER_UDP_PORT: u16 = 24602;
+const ECHO_PORT: u8 = 10;
+const REPLY_PORT: u8 = 11;
+
+/// Waits until UDP `port` is bound, as the driver binds it on its own thread.
+///
+/// Looks it up in the kernel socket tables, binding it here to find out would
+/// take the port away from the driver.
+fn wait_until_bound(port: u16) {
+    let suffix = format!(":{:04X}", port);
+    let is_bound = || {
+        ["/proc/net/udp", "/proc/net/udp6"].iter().any(|table| {
+            std::fs::read_to_string(table).is_ok_and(|table| {
+                table.lines().skip(1).any(|socket| {
+                    socket
+                        .split_whitespace()
+                        .nth(1)
+                        .is_some_and(|local| local.ends_with(&suffix))
+                })
+            })
+        })
+    };
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !is_bound() {
+        assert!(
+            Instant::now() < deadline,
+            "UDP port {} was never bound",
+            port
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_udp_two_processes() {
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(ADDRESS)).build();
+    csp_instance
+        .add_interface_route(
+            Route::new(PEER_ADDRESS),
+            CspUdpInterface::new("127.0.0.1", UDP_PORT, PEER_UDP_PORT),
+        )
+        .unwrap();
+    let replies = csp_instance
+        .open_server_socket(CspPort::port(REPLY_PORT))
+        .unwrap();
+    wait_until_bound(UDP_PORT);
+
+    // A port in use is reported when the interface is added
+    assert!(csp_instance
+        .add_interface(
+            CspUdpInterface::new("127.0.0.1", UDP_PORT, PEER_UDP_PORT).ifname("UDP_USED")
+        )
+        .is_err());
+
+    // UDP drops everything until the peer is bound, so wait for it
+    let mut peer = common::spawn_peer("udp_peer", PEER_ENV, "1");
+
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(PEER_ADDRESS, ECHO_PORT),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    connection.send_packet(b"Hello over UDP").unwrap();
+
+    let reply = replies
+        .accept_timeout(Duration::from_secs(5))
+        .expect("No reply from the peer node");
+    assert_eq!(reply.src().address, PEER_ADDRESS);
+    let packet = reply
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("Empty reply from the peer node");
+    assert_eq!(packet.as_slice(), b"Hello over UDP");
+
+    assert!(peer.0.wait().unwrap().success());
+}
+
+/// The peer node, sends every packet it receives on `ECHO_PORT` back to `REPLY_PORT`.
+#[test]
+#[ignore = "started by test_udp_two_processes"]
+fn udp_peer() {
+    if std::env::var_os(PEER_ENV).is_none() {
+        return;
+    }
+
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(PEER_ADDRESS)).build();
+    csp_instance
+        .add_interface_route(
+            Route::new(ADDRESS),
+            CspUdpInterface::new("127.0.0.1", PEER_UDP_PORT, UDP_PORT),
+        )
+        .unwrap();
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(ECHO_PORT))
+        .unwrap();
+    wait_until_bound(PEER_UDP_PORT);
+    println!("{}", common::PEER_READY);
+
+    let conn = socket
+        .accept_timeout(Duration::from_secs(5))
+        .expect("No connection received over UDP");
+    let packet = conn
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("No packet received over UDP");
+
+    let reply = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(ADDRESS, REPLY_PORT),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    reply.send_packet(packet.as_slice()).unwrap();
+}
@@ -0,0 +1,128 @@
+#![cfg(unix)]
+
+//! Nodes in separate processes, joined by a Unix socket broker.
+//! The peer node is this test binary again, running `unix_peer`.
+
+mod common;
+
+use libcsp::{
+    interface::{CspUnixBroker, CspUnixInterface},
+    CspConnAddress, CspConnPriority, CspPort, LibCspBuilder, LibCspConfig, Route,
+};
+use std::{os::unix::net::UnixDatagram, path::PathBuf, time::Duration};
+
+const PEER_ENV: &str = "LIBCSP_UNIX_BROKER";
+
+const ADDRESS: u16 = 1;
+const PEER_ADDRESS: u16 = 2;
+const RAW_ADDRESS: u16 = 3;
+const ECHO_PORT: u8 = 10;
+const REPLY_PORT: u8 = 11;
+
+#[test]
+fn test_unix_broker() {
+    let broker_path = std::env::temp_dir().join(format!("csp-broker-{}.sock", std::process::id()));
+    CspUnixBroker::bind(&broker_path).unwrap().spawn();
+
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(ADDRESS)).build();
+    csp_instance
+        .add_interface_route(
+            Route::default_address(),
+            CspUnixInterface::connect(&broker_path),
+        )
+        .unwrap();
+    let replies = csp_instance
+        .open_server_socket(CspPort::port(REPLY_PORT))
+        .unwrap();
+
+    // A member of the subnet that is not a CSP node, to see what the broker passes on
+    let raw_path = PathBuf::from(format!("{}.raw", broker_path.display()));
+    let raw = UnixDatagram::bind(&raw_path).unwrap();
+    raw.set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    raw.send_to(&RAW_ADDRESS.to_be_bytes(), &broker_path)
+        .unwrap();
+
+    // The peer has told the broker its address by the time it is ready
+    let mut peer = common::spawn_peer("unix_peer", PEER_ENV, &broker_path);
+
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(PEER_ADDRESS, ECHO_PORT),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    connection.send_packet(b"Hello over Unix").unwrap();
+
+    let reply = replies
+        .accept_timeout(Duration::from_secs(5))
+        .expect("No reply from the peer node");
+    assert_eq!(reply.src().address, PEER_ADDRESS);
+    let packet = reply
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("Empty reply from the peer node");
+    assert_eq!(packet.as_slice(), b"Hello over Unix");
+    assert!(peer.0.wait().unwrap().success());
+
+    // Only packets for its own address reach a member
+    let mut datagram = [0; 512];
+    assert!(raw.recv(&mut datagram).is_err());
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(RAW_ADDRESS, ECHO_PORT),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    connection.send_packet(b"Hello raw").unwrap();
+    let length = raw
+        .recv(&mut datagram)
+        .expect("Nothing reached the raw member");
+    assert_eq!(&datagram[..2], &RAW_ADDRESS.to_be_bytes());
+    assert!(datagram[..length].ends_with(b"Hello raw"));
+
+    let _ = std::fs::remove_file(raw_path);
+}
+
+/// The peer node, sends every packet it receives on `ECHO_PORT` back to `REPLY_PORT`.
+#[test]
+#[ignore = "started by test_unix_broker"]
+fn unix_peer() {
+    let Some(broker_path) = std::env::var_os(PEER_ENV) else {
+        return;
+    };
+
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(PEER_ADDRESS)).build();
+    csp_instance
+        .add_interface_route(
+            Route::default_address(),
+            CspUnixInterface::connect(broker_path),
+        )
+        .unwrap();
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(ECHO_PORT))
+        .unwrap();
+    println!("{}", common::PEER_READY);
+
+    let conn = socket
+        .accept_timeout(Duration::from_secs(5))
+        .expect("No connection received over the broker");
+    let packet = conn
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("No packet received over the broker");
+
+    let reply = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(ADDRESS, REPLY_PORT),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    reply.send_packet(packet.as_slice()).unwrap();
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "usart")]
+
+mod common;
+
+use libcsp::{
+    interface::CspUsartInterface, CspConnAddress, CspConnPriority, CspPort, LibCspBuilder,
+    LibCspConfig,
+};
+use std::{
+    ffi::CStr,
+    fs::File,
+    io::{Read, Write},
+    os::fd::{FromRawFd, OwnedFd},
+    thread,
+    time::Duration,
+};
+
+/// A pseudo-terminal pair, the slave end is what the USART driver opens.
+struct Pty {
+    master: File,
+    slave_path: String,
+    _slave: OwnedFd,
+}
+
+fn open_pty() -> Pty {
+    let mut master = 0;
+    let mut slave = 0;
+    let mut name = [0 as libc::c_char; 128];
+    unsafe {
+        let result = libc::openpty(
+            &mut master,
+            &mut slave,
+            name.as_mut_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+        );
+        assert_eq!(result, 0, "openpty failed");
+
+        Pty {
+            master: File::from_raw_fd(master),
+            slave_path: CStr::from_ptr(name.as_ptr()).to_str().unwrap().to_string(),
+            _slave: OwnedFd::from_raw_fd(slave),
+        }
+    }
+}
+
+/// Copies everything written on one serial line to the other, like a null modem cable.
+fn bridge(mut from: File, mut to: File) {
+    thread::spawn(move || {
+        let mut buffer = [0; 256];
+        while let Ok(read) = from.read(&mut buffer) {
+            if read == 0 || to.write_all(&buffer[..read]).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[test]
+fn test_kiss_over_pty() {
+    let address = 1;
+    let remote_address = 2;
+    let port = 10;
+
+    let pty_a = open_pty();
+    let pty_b = open_pty();
+    bridge(
+        pty_a.master.try_clone().unwrap(),
+        pty_b.master.try_clone().unwrap(),
+    );
+    bridge(
+        pty_b.master.try_clone().unwrap(),
+        pty_a.master.try_clone().unwrap(),
+    );
+
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+
+    // The two pseudo-terminals are bridged into one serial line
+    let (kiss_a, _) = common::add_looped_link(
+        &csp_instance,
+        remote_address,
+        CspUsartInterface::new(&pty_a.slave_path, 115200)
+            .ifname("KISS_A")
+            .address(address),
+        CspUsartInterface::new(&pty_b.slave_path, 115200)
+            .ifname("KISS_B")
+            .address(remote_address),
+    );
+
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(remote_address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+    connection.send_packet(b"Hello over KISS").unwrap();
+
+    let conn = socket
+        .accept_timeout(Duration::from_secs(2))
+        .expect("No connection received over KISS");
+    let packet = conn
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("No packet received over KISS");
+    assert_eq!(packet.as_slice(), b"Hello over KISS");
+
+    let kiss_a = kiss_a.info();
+    assert_eq!(kiss_a.name, "KISS_A");
+    assert_eq!(kiss_a.stats.tx, 1);
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "yaml")]
+
+use libcsp::{CspErrorKind, LibCspBuilder, LibCspConfig, Route};
+use std::path::PathBuf;
+
+fn write_config(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("libcsp-rs-{}-{}.yaml", name, std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn build_error(name: &str, contents: &str) -> String {
+    let path = write_config(name, contents);
+    let result = LibCspBuilder::new(LibCspConfig::new(1)).build_with_yaml(&path);
+    std::fs::remove_file(&path).ok();
+
+    let error = result.err().expect("Bad config was accepted");
+    assert!(matches!(error.kind, CspErrorKind::Inval));
+    error.message
+}
+
+#[test]
+fn test_yaml_rejects_bad_entries() {
+    let message = build_error(
+        "unknown-driver",
+        "- name: A\n  driver: pigeon\n  address: 1\n",
+    );
+    assert!(message.contains("unknown driver `pigeon`"), "{}", message);
+
+    let message = build_error("missing-address", "- name: A\n  driver: zmq\n");
+    assert!(message.contains("missing `address`"), "{}", message);
+
+    let message = build_error(
+        "bad-netmask",
+        "- name: A\n  driver: zmq\n  address: 1\n  netmask: 20\n",
+    );
+    assert!(message.contains("interface #1"), "{}", message);
+    assert!(message.contains("`netmask` 20"), "{}", message);
+
+    let message = build_error(
+        "duplicate",
+        "- name: A\n  driver: zmq\n  address: 1\n- name: A\n  driver: zmq\n  address: 2\n",
+    );
+    assert!(message.contains("duplicate name `A`"), "{}", message);
+
+    let message = build_error("typo", "- name: A\n  driver: zmq\n  adress: 1\n");
+    assert!(message.contains("missing `address`"), "{}", message);
+
+    let message = build_error("not-a-list", "name: A\n");
+    assert!(
+        message.contains("expected a list of interfaces"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_yaml_builds_node() {
+    // The hub doesn't need to be running for the interface to be created
+    let path = write_config(
+        "valid",
+        "- name: ZMQ_YAML\n  driver: zmq\n  server: localhost\n  address: 5\n  netmask: 8\n  default: true\n",
+    );
+    let result = LibCspBuilder::new(LibCspConfig::new(5)).build_with_yaml(&path);
+    std::fs::remove_file(&path).ok();
+    let csp_instance = result.unwrap();
+
+    let interface = csp_instance.interface("ZMQ_YAML").unwrap().info();
+    assert_eq!(interface.address, 5);
+    assert!(interface.is_default);
+
+    // This node is still reached through the loopback interface
+    let (_, name) = csp_instance.find_route(5).unwrap();
+    assert_eq!(name, "LOOP");
+    let (route, name) = csp_instance.find_route(6).unwrap();
+    assert_eq!(route, Route::new(5).netmask_bits(8));
+    assert_eq!(name, "ZMQ_YAML");
+    let (route, name) = csp_instance.find_route(1000).unwrap();
+    assert_eq!(route, Route::default_address());
+    assert_eq!(name, "ZMQ_YAML");
+}
+
+#[test]
+fn test_yaml_missing_file() {
+    let result = LibCspBuilder::new(LibCspConfig::new(1)).build_with_yaml("/nonexistent/csp.yaml");
+    assert!(result.is_err());
+}
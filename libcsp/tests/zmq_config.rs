@@ -0,0 +1,43 @@
+use libcsp::interface::CspZmqConfig;
+
+#[test]
+fn test_zmq_config_validation() {
+    CspZmqConfig::host("localhost").validate().unwrap();
+    CspZmqConfig::endpoints("tcp://*:6000", "ipc:///tmp/csp")
+        .ifname("HUB")
+        .address(5)
+        .rx_filter([5, 6])
+        .validate()
+        .unwrap();
+
+    assert_eq!(
+        CspZmqConfig::host("10.0.0.1").publish_endpoint(),
+        "tcp://10.0.0.1:6000"
+    );
+
+    let error = |config: CspZmqConfig| config.validate().unwrap_err().message;
+
+    let bad_endpoints = [
+        ("localhost:6000", "expected `transport://address`"),
+        ("udp://localhost:6000", "unknown transport `udp`"),
+        ("tcp://", "the address is empty"),
+        ("tcp://localhost", "need a port"),
+        ("tcp://:6000", "the host is empty"),
+        ("tcp://localhost:port", "`port` is not a valid port"),
+        ("tcp://localhost:70000", "`70000` is not a valid port"),
+        ("tcp://local host:6000", "can't contain spaces"),
+    ];
+    for (endpoint, reason) in bad_endpoints {
+        let message = error(CspZmqConfig::endpoints(endpoint, "tcp://localhost:7000"));
+        assert!(
+            message.contains("publish endpoint") && message.contains(reason),
+            "{}: {}",
+            endpoint,
+            message
+        );
+    }
+    assert!(error(CspZmqConfig::endpoints("tcp://localhost:6000", "tcp:/x")).contains("subscribe"));
+
+    assert!(error(CspZmqConfig::host("localhost").ifname("")).contains("name"));
+    assert!(error(CspZmqConfig::host("localhost").rx_filter([0x4000])).contains("16384"));
+}
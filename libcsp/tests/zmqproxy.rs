@@ -0,0 +1,88 @@
+#![cfg(feature = "zmqproxy")]
+
+use libcsp::{
+    interface::CspZmqConfig, zmqproxy::CspZmqProxy, CspConnAddress, CspConnPriority, CspPort,
+    LibCspBuilder, LibCspConfig, Route,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[test]
+fn test_zmqproxy() {
+    let address = 1;
+    let remote_address = 2;
+    let port = 10;
+
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let proxy = {
+        let captured = captured.clone();
+        CspZmqProxy::new()
+            .subscribe_endpoint("tcp://127.0.0.1:16000")
+            .publish_endpoint("tcp://127.0.0.1:17000")
+            .log_traffic(true)
+            .capture(move |message| captured.lock().unwrap().push(message.to_vec()))
+            .spawn()
+            .unwrap()
+    };
+
+    // The endpoints are taken while the proxy runs
+    assert!(CspZmqProxy::new()
+        .subscribe_endpoint("tcp://127.0.0.1:16000")
+        .publish_endpoint("tcp://127.0.0.1:17001")
+        .spawn()
+        .is_err());
+
+    // Both ends of the hub on the same node, the remote address is received on ZMQ_B
+    let csp_instance = LibCspBuilder::new(LibCspConfig::new(address)).build();
+    let hub = CspZmqConfig::endpoints("tcp://127.0.0.1:16000", "tcp://127.0.0.1:17000");
+    csp_instance
+        .add_interface_route(
+            Route::new(remote_address),
+            hub.clone().ifname("ZMQ_A").rx_filter([address]),
+        )
+        .unwrap();
+    csp_instance
+        .add_interface(
+            hub.ifname("ZMQ_B")
+                .address(remote_address)
+                .rx_filter([remote_address]),
+        )
+        .unwrap();
+
+    let socket = csp_instance
+        .open_server_socket(CspPort::port(port))
+        .unwrap();
+    let connection = csp_instance
+        .client()
+        .connect(
+            CspConnAddress::new(remote_address, port),
+            CspConnPriority::Normal,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+    // Packets are lost until the subscriptions have reached the proxy
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let conn = loop {
+        assert!(Instant::now() < deadline, "Nothing crossed the ZMQ proxy");
+        connection.send_packet(b"Hello proxy").unwrap();
+        if let Some(conn) = socket.accept_timeout(Duration::from_millis(100)) {
+            break conn;
+        }
+    };
+    let packet = conn
+        .iter_packets(Duration::from_secs(1))
+        .next()
+        .expect("Empty packet from the ZMQ proxy");
+    assert_eq!(packet.as_slice(), b"Hello proxy");
+
+    proxy.stop();
+
+    // Every message starts with the next hop address
+    let captured = captured.lock().unwrap();
+    assert!(captured.iter().any(|message| {
+        message.starts_with(&remote_address.to_be_bytes()) && message.ends_with(b"Hello proxy")
+    }));
+}